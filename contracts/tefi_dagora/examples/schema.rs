@@ -3,8 +3,8 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use tefi_dagora::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, ThreadsResponse, CommentsResponse};
-use tefi_dagora::state::{Thread, Comment, Config};
+use tefi_dagora::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, ThreadsResponse, CommentsResponse, ModeratorsResponse, ModeratorCategoriesResponse, CategoriesResponse, RevisionsResponse, CommentTreeResponse, VoteResponse, ReactionCountsResponse, ReactionsResponse, TipsResponse, Cw20FeesResponse, ClaimableResponse, CountsResponse, BansResponse};
+use tefi_dagora::state::{Thread, Comment, Config, PendingAdmin};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -15,9 +15,24 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
     export_schema(&schema_for!(Thread), &out_dir);
     export_schema(&schema_for!(Comment), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
+    export_schema(&schema_for!(PendingAdmin), &out_dir);
     export_schema(&schema_for!(ThreadsResponse), &out_dir);
     export_schema(&schema_for!(CommentsResponse), &out_dir);
+    export_schema(&schema_for!(ModeratorsResponse), &out_dir);
+    export_schema(&schema_for!(ModeratorCategoriesResponse), &out_dir);
+    export_schema(&schema_for!(CategoriesResponse), &out_dir);
+    export_schema(&schema_for!(RevisionsResponse), &out_dir);
+    export_schema(&schema_for!(CommentTreeResponse), &out_dir);
+    export_schema(&schema_for!(VoteResponse), &out_dir);
+    export_schema(&schema_for!(ReactionCountsResponse), &out_dir);
+    export_schema(&schema_for!(ReactionsResponse), &out_dir);
+    export_schema(&schema_for!(TipsResponse), &out_dir);
+    export_schema(&schema_for!(Cw20FeesResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableResponse), &out_dir);
+    export_schema(&schema_for!(CountsResponse), &out_dir);
+    export_schema(&schema_for!(BansResponse), &out_dir);
 }
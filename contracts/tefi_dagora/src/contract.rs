@@ -1,21 +1,162 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Order, Addr, Uint128, CosmosMsg, BankMsg, Coin};
-use cw2::set_contract_version;
-use cw_storage_plus::Bound;
+use cosmwasm_std::{from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Order, Addr, Uint128, Decimal, CosmosMsg, BankMsg, WasmMsg, Coin, Event, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, PrimaryKey};
+use cw_utils::Expiration;
+use semver::Version;
+use std::collections::HashMap;
 
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, GetThreadByIdResponse, ThreadsResponse, CommentsResponse, MigrateMsg};
-use crate::state::{ CONFIG, Config, THREAD_COUNTER, Thread, threads, next_thread_counter, COMMENT_COUNTER, Comment, comments, next_comment_counter };
+use crate::msg::{ ExecuteMsg, ReceiveMsg, InstantiateMsg, QueryMsg, SortOrder, ThreadsResponse, CommentsResponse, ModeratorsResponse, ModeratorCategoriesResponse, CategoriesResponse, RevisionsResponse, CommentNode, CommentTreeResponse, VoteResponse, ReactionCount, ReactionCountsResponse, ReactionsResponse, TipsResponse, Cw20FeesResponse, ClaimableResponse, CountsResponse, BansResponse, MigrateMsg};
+use crate::state::{ comment_parent_key, LEGACY_FEE_DENOM, BANS, Ban, PINS, PinScope, GLOBAL_PIN_SCOPE, MAX_PINS, CONFIG, Config, ContentLimits, LengthLimit, FeeSplit, PENDING_ADMIN, PendingAdmin, LEGACY_CONFIG, UPGRADE_PROGRESS, UpgradeProgress, CW20_FEES, Cw20Fee, CLAIMABLE, CLAIMABLE_CW20, TOTAL_CLAIMABLE, TOTAL_CLAIMABLE_CW20, CascadePolicy, REVISIONS, Revision, Target, VOTES, VoteDirection, ALLOWED_REACTIONS, REACTIONS, REACTION_COUNTS, TIP_TOTALS, AUTHOR_TIPS, CATEGORIES, Category, category_key, MODERATORS, Moderator, ModeratorScope, moderator_scopes, THREAD_COUNTER, Thread, threads, next_thread_counter, COMMENT_COUNTER, Comment, comments, next_comment_counter, LIVE_THREADS, LIVE_COMMENTS, adjust_live_count };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:tefi_dagora";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Comments removed per transaction by the delete cascade
+const CASCADE_BATCH: u32 = 30;
+// Threads and comments upgraded per transaction by migrate and MigrateStep
+const UPGRADE_BATCH: u32 = 100;
 
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContractName { name: stored.contract });
+    }
+    let from_version = parse_version(&stored.version)?;
+    if from_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade { stored: stored.version, current: CONTRACT_VERSION.to_string() });
+    }
+
+    let mut response = Response::new()
+    .add_attribute("method", "migrate")
+    .add_attribute("from_version", stored.version)
+    .add_attribute("to_version", CONTRACT_VERSION);
+
+    // Each step upgrades state written by releases older than the one it is gated on
+    if from_version < Version::new(0, 2, 0) {
+        upgrade_config(deps.storage, msg.legacy_fee_denom)?;
+        LIVE_THREADS.save(deps.storage, &0)?;
+        LIVE_COMMENTS.save(deps.storage, &0)?;
+        UPGRADE_PROGRESS.save(deps.storage, &UpgradeProgress { threads_done: false, last_id: None })?;
+        response = upgrade_records(deps.storage, &env, UPGRADE_BATCH, response)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(response)
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidVersion { version: version.to_string() })
+}
+
+// Converts the 0.1.0 config with single Uint128 fees into fee schedules
+// in the denom given to migrate.
+fn upgrade_config(store: &mut dyn Storage, legacy_fee_denom: Option<String>) -> StdResult<()> {
+    if CONFIG.load(store).is_ok() {
+        return Ok(());
+    }
+    let legacy = LEGACY_CONFIG.load(store)?;
+    let denom = legacy_fee_denom.unwrap_or_else(|| LEGACY_FEE_DENOM.to_string());
+    let schedule = |amount: Uint128| if amount.is_zero() { vec![] } else { vec![Coin { denom: denom.clone(), amount }] };
+    let config = Config {
+        thread_fee: schedule(legacy.thread_fee),
+        comment_fee: schedule(legacy.comment_fee),
+        admin_addr: Some(legacy.admin_addr),
+        comment_cascade: CascadePolicy::default(),
+        tip_cut: Decimal::zero(),
+        fee_split: FeeSplit::default(),
+        content_limits: ContentLimits::default(),
+    };
+    CONFIG.save(store, &config)
+}
+
+// Upgrades up to limit records, all threads first and then all comments, in a single pass each.
+// Records saved before timestamps existed have a zero created_height and get the current block,
+// which is an upper bound of their real age. Every comment is saved again so indexes added after
+// it was stored get their entries, and the live counters are seeded along the way.
+fn upgrade_records(store: &mut dyn Storage, env: &Env, limit: u32, response: Response) -> StdResult<Response> {
+    let mut progress = UPGRADE_PROGRESS.load(store)?;
+    let mut budget = limit as usize;
+    let (mut threads_stamped, mut comments_stamped, mut comments_indexed): (u64, u64, u64) = (0, 0, 0);
+
+    if !progress.threads_done {
+        let cursor = progress.last_id.map(|id| id.to_be_bytes());
+        let batch = threads()
+        .range(store, cursor.as_ref().map(|id| Bound::exclusive(id.as_slice())), None, Order::Ascending)
+        .take(budget)
+        .collect::<StdResult<Vec<_>>>()?;
+        budget -= batch.len();
+        progress.last_id = batch.last().map(|(_, thread)| thread.id).or(progress.last_id);
+        if budget > 0 {
+            progress = UpgradeProgress { threads_done: true, last_id: None };
+        }
+        for (key, thread) in batch {
+            if !thread.deleted {
+                adjust_live_count(store, &LIVE_THREADS, 1, 0)?;
+            }
+            if thread.created_height == 0 {
+                let thread = Thread { created_at: env.block.time, created_height: env.block.height, updated_at: env.block.time, ..thread };
+                threads().save(store, &key, &thread)?;
+                threads_stamped += 1;
+            }
+        }
+    }
+
+    let mut done = false;
+    if progress.threads_done && budget > 0 {
+        let cursor = progress.last_id.map(|id| id.to_be_bytes());
+        let batch = comments()
+        .range(store, cursor.as_ref().map(|id| Bound::exclusive(id.as_slice())), None, Order::Ascending)
+        .take(budget)
+        .collect::<StdResult<Vec<_>>>()?;
+        done = batch.len() < budget;
+        progress.last_id = batch.last().map(|(_, comment)| comment.comment_id).or(progress.last_id);
+        for (key, comment) in batch {
+            if !comment.deleted {
+                adjust_live_count(store, &LIVE_COMMENTS, 1, 0)?;
+            }
+            let comment = if comment.created_height == 0 {
+                comments_stamped += 1;
+                Comment { created_at: env.block.time, created_height: env.block.height, updated_at: env.block.time, ..comment }
+            } else {
+                comment
+            };
+            comments().save(store, &key, &comment)?;
+            comments_indexed += 1;
+        }
+    }
+
+    let mut response = response
+    .add_attribute("threads_stamped", threads_stamped.to_string())
+    .add_attribute("comments_stamped", comments_stamped.to_string())
+    .add_attribute("comments_indexed", comments_indexed.to_string())
+    .add_attribute("done", done.to_string());
+    if done {
+        UPGRADE_PROGRESS.remove(store);
+        response = response
+        .add_attribute("live_threads", LIVE_THREADS.load(store)?.to_string())
+        .add_attribute("live_comments", LIVE_COMMENTS.load(store)?.to_string());
+    } else {
+        UPGRADE_PROGRESS.save(store, &progress)?;
+    }
+    Ok(response)
+}
+
+// Anyone can push the upgrade forward, it only rewrites records into their new shape
+pub fn migrate_step(deps: DepsMut, env: Env, info: MessageInfo, limit: Option<u32>) -> Result<Response, ContractError> {
+    if UPGRADE_PROGRESS.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoUpgradePending {});
+    }
+    let response = Response::new()
+    .add_attribute("method", "migrate_step")
+    .add_attribute("sender", info.sender);
+    let limit = limit.unwrap_or(UPGRADE_BATCH).min(UPGRADE_BATCH);
+    Ok(upgrade_records(deps.storage, &env, limit, response)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -27,14 +168,25 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
 
     let config: Config = Config {
-        thread_fee: msg.thread_fee.unwrap_or_else(|| Uint128::zero()),
-        comment_fee: msg.comment_fee.unwrap_or_else(|| Uint128::zero()),
-        admin_addr: info.sender.clone(),
+        thread_fee: msg.thread_fee.unwrap_or_default(),
+        comment_fee: msg.comment_fee.unwrap_or_default(),
+        admin_addr: Some(info.sender.clone()),
+        comment_cascade: msg.comment_cascade.unwrap_or_default(),
+        tip_cut: msg.tip_cut.unwrap_or_default(),
+        fee_split: FeeSplit::default(),
+        content_limits: ContentLimits::default(),
     };
+    if config.tip_cut > Decimal::one() {
+        return Err(ContractError::InvalidTipCut {});
+    }
+    validate_fee_schedule(&config.thread_fee)?;
+    validate_fee_schedule(&config.comment_fee)?;
 
     CONFIG.save(deps.storage, &config)?;
     COMMENT_COUNTER.save(deps.storage, &0)?;
     THREAD_COUNTER.save(deps.storage, &0)?;
+    LIVE_THREADS.save(deps.storage, &0)?;
+    LIVE_COMMENTS.save(deps.storage, &0)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -50,544 +202,3576 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Records are only partly upgraded until MigrateStep is done
+    if UPGRADE_PROGRESS.may_load(deps.storage)?.is_some() && !matches!(msg, ExecuteMsg::MigrateStep { .. }) {
+        return Err(ContractError::UpgradePending {});
+    }
     match msg {
-        ExecuteMsg::CreateThread {title, content, category} => create_thread(deps, info, title, content, category),
-        ExecuteMsg::UpdateThread { id, title, content } => update_thread(deps, info, id, title, content),
-        ExecuteMsg::UpdateThreadContent { id, content } => update_thread_content(deps, info, id, content),
-        ExecuteMsg::UpdateThreadTitle { id, title } => update_thread_title(deps, info, id, title),
-        ExecuteMsg::AddComment { thread_id, comment } => add_comment(deps, info, thread_id, comment),
-        ExecuteMsg::UpdateComment { comment_id, comment } => update_comment(deps, info, comment_id, comment),
-        ExecuteMsg::Send { address, amount } => send(deps, env, info, address, amount),
-        ExecuteMsg::UpdateFees {thread_fee, comment_fee} => update_fees(deps, info, thread_fee, comment_fee)
+        ExecuteMsg::MigrateStep { limit } => migrate_step(deps, env, info, limit),
+        ExecuteMsg::CreateThread {title, content, category} => create_thread(deps, env, info, title, content, category),
+        ExecuteMsg::UpdateThread { id, title, content } => update_thread(deps, env, info, id, title, content),
+        ExecuteMsg::UpdateThreadContent { id, content } => update_thread_content(deps, env, info, id, content),
+        ExecuteMsg::UpdateThreadTitle { id, title } => update_thread_title(deps, env, info, id, title),
+        ExecuteMsg::AddComment { thread_id, comment, parent_comment_id } => add_comment(deps, env, info, thread_id, comment, parent_comment_id),
+        ExecuteMsg::UpdateComment { comment_id, comment } => update_comment(deps, env, info, comment_id, comment),
+        ExecuteMsg::Send { address, amount, denom } => send(deps, env, info, address, amount, denom),
+        ExecuteMsg::UpdateFees {thread_fee, comment_fee, tip_cut} => update_fees(deps, info, thread_fee, comment_fee, tip_cut),
+        ExecuteMsg::DeleteThread { id, hard } => delete_thread(deps, env, info, id, hard.unwrap_or(false)),
+        ExecuteMsg::PurgeComments { thread_id, limit } => purge_thread_comments(deps, info, thread_id, limit),
+        ExecuteMsg::DeleteComment { comment_id, hard } => delete_comment(deps, env, info, comment_id, hard.unwrap_or(false)),
+        ExecuteMsg::UpdateCascadePolicy { comment_cascade } => update_cascade_policy(deps, info, comment_cascade),
+        ExecuteMsg::MoveThread { id, category } => move_thread(deps, env, info, id, category),
+        ExecuteMsg::AddModerator { address, categories } => add_moderator(deps, info, address, categories),
+        ExecuteMsg::RemoveModerator { address } => remove_moderator(deps, info, address),
+        ExecuteMsg::CreateCategory { name, description, sort_order, owner } => create_category(deps, info, name, description, sort_order, owner),
+        ExecuteMsg::UpdateCategory { name, description, sort_order, archived, owner, clear_owner } => {
+            let owner = if clear_owner.unwrap_or(false) { Some(None) } else { owner.map(Some) };
+            update_category(deps, info, name, description, sort_order, archived, owner)
+        },
+        ExecuteMsg::ArchiveCategory { name } => update_category(deps, info, name, None, None, Some(true), None),
+        ExecuteMsg::Vote { target, id, direction } => vote(deps, env, info, target, id, direction),
+        ExecuteMsg::React { target, id, reaction } => react(deps, env, info, target, id, reaction),
+        ExecuteMsg::UpdateAllowedReactions { reactions } => update_allowed_reactions(deps, info, reactions),
+        ExecuteMsg::Tip { target, id } => tip(deps, info, target, id),
+        ExecuteMsg::Receive(wrapper) => receive_cw20(deps, env, info, wrapper),
+        ExecuteMsg::UpdateCw20Fee { token, thread_fee, comment_fee } => update_cw20_fee(deps, info, token, thread_fee, comment_fee),
+        ExecuteMsg::RemoveCw20Fee { token } => remove_cw20_fee(deps, info, token),
+        ExecuteMsg::SendCw20 { token, address, amount } => send_cw20(deps, env, info, token, address, amount),
+        ExecuteMsg::UpdateFeeSplit { fee_split } => update_fee_split(deps, info, fee_split),
+        ExecuteMsg::ClaimRewards {} => claim_rewards(deps, info),
+        ExecuteMsg::ProposeNewAdmin { address, expires } => propose_new_admin(deps, env, info, address, expires),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, env, info),
+        ExecuteMsg::CancelAdminTransfer {} => cancel_admin_transfer(deps, info),
+        ExecuteMsg::RenounceAdmin {} => renounce_admin(deps, info),
+        ExecuteMsg::UpdateContentLimits { content_limits } => update_content_limits(deps, info, content_limits),
+        ExecuteMsg::PinThread { id, scope } => pin_thread(deps, env, info, id, scope),
+        ExecuteMsg::UnpinThread { id, scope } => unpin_thread(deps, info, id, scope),
+        ExecuteMsg::LockThread { id } => set_thread_lock(deps, env, info, id, true),
+        ExecuteMsg::UnlockThread { id } => set_thread_lock(deps, env, info, id, false),
+        ExecuteMsg::Ban { address, category, expires, reason } => ban(deps, env, info, address, category, expires, reason),
+        ExecuteMsg::Unban { address } => unban(deps, info, address)
     }
 }
 
-pub fn create_thread(deps: DepsMut, info: MessageInfo, title: String, content: String, category: String) ->Result<Response, ContractError> {
+pub fn create_thread(deps: DepsMut, env: Env, info: MessageInfo, title: String, content: String, category: String) ->Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    
-    let coin_amount: Uint128 = info
-    .funds
-    .iter()
-    .find(|c| c.denom == "uluna")
-    .map(|c| Uint128::from(c.amount))
-    .unwrap_or_else(Uint128::zero);
-
-    if config.thread_fee > coin_amount {
-        return Err(ContractError::LessFeeAmount {  });
+    let (charged, refund) = check_fee(&info.funds, &config.thread_fee)?;
+    let (thread, response) = save_thread(deps.storage, &env, &config.content_limits, info.sender.clone(), title, content, category)?;
+    for coin in &charged {
+        split_fee(deps.storage, &config, &thread, false, &FeeAsset::Native(coin.denom.clone()), coin.amount)?;
     }
+    Ok(settle_fee(response, &info.sender, &charged, refund))
+}
 
-    let thread_id = next_thread_counter(deps.storage)?; 
+// Stores a new thread once its fee has been paid, natively or through a CW20 hook
+fn save_thread(store: &mut dyn Storage, env: &Env, limits: &ContentLimits, author: Addr, title: String, content: String, category: String) -> Result<(Thread, Response), ContractError> {
+    validate_text("title", &title, &limits.title, true, false)?;
+    validate_text("content", &content, &limits.content, false, true)?;
+    validate_text("category", &category, &limits.category, false, false)?;
+    let category = open_category(store, &category)?;
+    check_not_banned(store, env, &author, &category)?;
+
+    let thread_id = next_thread_counter(store)?; 
     let thread = Thread {
         id: thread_id,
         title,
         content: String::from(&content),
         category,
-        author: info.sender.clone(),
+        author: author.clone(),
+        deleted: false,
+        created_at: env.block.time,
+        created_height: env.block.height,
+        updated_at: env.block.time,
+        revision: 0,
+        upvotes: 0,
+        downvotes: 0,
+        pinned: false,
+        locked: false,
+        locked_by: None,
     };
-   threads().save(deps.storage, &thread_id.to_be_bytes().to_vec(), &thread)?;
-    Ok(
+   threads().save(store, &thread_id.to_be_bytes(), &thread)?;
+    adjust_live_count(store, &LIVE_THREADS, 1, 0)?;
+    Ok((
+        thread,
         Response::new()
         .add_attribute("method", "create_thread")
-        .add_attribute("author", info.sender)
+        .add_attribute("author", author)
         .add_attribute("message", content)
         .add_attribute("thread_id", thread_id.to_string())
-    )
+    ))
     
 }
 
-pub fn update_thread(deps: DepsMut, info: MessageInfo, id: u64, title: String, content: String) -> Result<Response, ContractError> {
-    threads().update(deps.storage, &id.to_be_bytes(), |old| match old {
-        Some(thread) => {
-            if info.sender != thread.author {
-                return Err(ContractError::Unauthorized { });
-            }
-           let updated_thread = Thread {
-            content: content.clone(),
-            title: title.clone(),
-            ..thread
-           };
-           Ok(updated_thread)
-        } ,
-        None => Err(ContractError::ThreadNotExists {}),
-    })?;
+pub fn update_thread(deps: DepsMut, env: Env, info: MessageInfo, id: u64, title: String, content: String) -> Result<Response, ContractError> {
+    let thread = edit_thread(deps.storage, &env, &info.sender, id, Some(title.clone()), Some(content.clone()))?;
     Ok(
         Response::new()
         .add_attribute("method", "update_thread")
         .add_attribute("author", info.sender)
+        .add_attribute("revision", thread.revision.to_string())
         .add_attribute("title", title)
         .add_attribute("content", content),
     )
 }
 
-pub fn update_thread_content(deps: DepsMut, info: MessageInfo, id: u64, content: String) -> Result<Response, ContractError> {
-    threads().update(deps.storage, &id.to_be_bytes(), |old| match old {
-        Some(thread) => {
-            if info.sender != thread.author {
-                return Err(ContractError::Unauthorized { });
-            }
-           let updated_thread = Thread {
-            content: content.clone(),
-            ..thread
-           };
-           Ok(updated_thread)
-        } ,
-        None => Err(ContractError::ThreadNotExists {}),
-    })?;
+pub fn update_thread_content(deps: DepsMut, env: Env, info: MessageInfo, id: u64, content: String) -> Result<Response, ContractError> {
+    let thread = edit_thread(deps.storage, &env, &info.sender, id, None, Some(content.clone()))?;
     Ok(
         Response::new()
         .add_attribute("method", "update_thread_content")
         .add_attribute("author", info.sender)
+        .add_attribute("revision", thread.revision.to_string())
         .add_attribute("content", content),
     )
 }
 
-pub fn update_thread_title(deps: DepsMut, info: MessageInfo, id: u64, title: String) -> Result<Response, ContractError> {
-    threads().update(deps.storage, &id.to_be_bytes(), |old| match old {
-        Some(thread) => {
-            if info.sender != thread.author {
-                return Err(ContractError::Unauthorized { });
-            }
-           let updated_thread = Thread {
-            title: title.clone(),
-            ..thread
-           };
-           Ok(updated_thread)
-        } ,
-        None => Err(ContractError::ThreadNotExists {}),
-    })?;
+pub fn update_thread_title(deps: DepsMut, env: Env, info: MessageInfo, id: u64, title: String) -> Result<Response, ContractError> {
+    let thread = edit_thread(deps.storage, &env, &info.sender, id, Some(title.clone()), None)?;
     Ok(
         Response::new()
         .add_attribute("method", "update_thread_title")
         .add_attribute("author", info.sender)
+        .add_attribute("revision", thread.revision.to_string())
         .add_attribute("title", title),
     )
 }
 
-pub fn add_comment(deps: DepsMut, info: MessageInfo, thread_id: u64, comment: String) -> Result<Response, ContractError> {  
-    let config = CONFIG.load(deps.storage)?;
-    
-    let coin_amount: Uint128 = info
-    .funds
+// Text must fit the configured length, non_blank fields need more than whitespace
+// and only multiline fields may contain line breaks and tabs
+fn validate_text(field: &str, value: &str, limit: &LengthLimit, non_blank: bool, multiline: bool) -> Result<(), ContractError> {
+    if non_blank && value.trim().is_empty() {
+        return Err(ContractError::BlankField { field: field.to_string() });
+    }
+    let length = value.chars().count();
+    if length < limit.min as usize {
+        return Err(ContractError::TooShort { field: field.to_string(), min: limit.min });
+    }
+    if length > limit.max as usize {
+        return Err(ContractError::TooLong { field: field.to_string(), max: limit.max });
+    }
+    if value.chars().any(|c| c.is_control() && !(multiline && matches!(c, '\n' | '\r' | '\t'))) {
+        return Err(ContractError::ControlCharacter { field: field.to_string() });
+    }
+    Ok(())
+}
+
+// Each fee option needs a distinct denom and a non-zero amount
+fn validate_fee_schedule(fee: &[Coin]) -> Result<(), ContractError> {
+    for (i, coin) in fee.iter().enumerate() {
+        if coin.amount.is_zero() || fee[..i].iter().any(|other| other.denom == coin.denom) {
+            return Err(ContractError::InvalidFeeSchedule { denom: coin.denom.clone() });
+        }
+    }
+    Ok(())
+}
+
+// Funds may only hold denoms from the fee schedule, and one of its options must be covered.
+// An empty schedule means posting is free.
+// Returns the fee option charged and the surplus to refund to the sender
+fn check_fee(funds: &[Coin], fee: &[Coin]) -> Result<(Vec<Coin>, Vec<Coin>), ContractError> {
+    if let Some(coin) = funds.iter().find(|c| !c.amount.is_zero() && !fee.iter().any(|f| f.denom == c.denom)) {
+        return Err(ContractError::InvalidFeeDenom { denom: coin.denom.clone() });
+    }
+    if fee.is_empty() {
+        return Ok((vec![], vec![]));
+    }
+
+    let mut paid: Vec<Coin> = vec![];
+    for coin in funds.iter().filter(|c| !c.amount.is_zero()) {
+        match paid.iter_mut().find(|p| p.denom == coin.denom) {
+            Some(p) => p.amount += coin.amount,
+            None => paid.push(coin.clone()),
+        }
+    }
+
+    let charged = fee
     .iter()
-    .find(|c| c.denom == "uluna")
-    .map(|c| Uint128::from(c.amount))
-    .unwrap_or_else(Uint128::zero);
+    .find(|option| paid.iter().any(|p| p.denom == option.denom && p.amount >= option.amount))
+    .ok_or(ContractError::LessFeeAmount {  })?;
+
+    // Everything beyond the charged option goes back, including other accepted denoms
+    let refund: Vec<Coin> = paid
+    .into_iter()
+    .map(|mut p| {
+        if p.denom == charged.denom {
+            p.amount -= charged.amount;
+        }
+        p
+    })
+    .filter(|p| !p.amount.is_zero())
+    .collect();
+
+    Ok((vec![charged.clone()], refund))
+}
+
+// Reports the fee and returns any overpayment in the same response
+fn settle_fee(response: Response, sender: &Addr, charged: &[Coin], refund: Vec<Coin>) -> Response {
+    let response = response
+    .add_attribute("fee_charged", coins_to_string(charged))
+    .add_attribute("fee_refunded", coins_to_string(&refund));
+    if refund.is_empty() {
+        return response;
+    }
+    response.add_message(BankMsg::Send { to_address: sender.to_string(), amount: refund })
+}
+
+// Asset a fee was paid in, a native denom or a whitelisted CW20 token
+enum FeeAsset {
+    Native(String),
+    Cw20(Addr),
+}
+
+fn accrue(store: &mut dyn Storage, recipient: &Addr, asset: &FeeAsset, amount: Uint128) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let add = |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) };
+    match asset {
+        FeeAsset::Native(denom) => {
+            CLAIMABLE.update(store, (recipient, denom), add)?;
+            TOTAL_CLAIMABLE.update(store, denom, add)?;
+        },
+        FeeAsset::Cw20(token) => {
+            CLAIMABLE_CW20.update(store, (recipient, token), add)?;
+            TOTAL_CLAIMABLE_CW20.update(store, token, add)?;
+        },
+    }
+    Ok(())
+}
+
+// Credits the configured shares of a charged fee, the rest stays with the contract
+fn split_fee(store: &mut dyn Storage, config: &Config, thread: &Thread, is_comment: bool, asset: &FeeAsset, amount: Uint128) -> StdResult<()> {
+    let split = &config.fee_split;
+    if let Some(treasury) = &split.treasury {
+        accrue(store, treasury, asset, amount * split.treasury_share)?;
+    }
+    if is_comment {
+        accrue(store, &thread.author, asset, amount * split.thread_author_share)?;
+    }
+    if let Some(owner) = CATEGORIES.may_load(store, &category_key(&thread.category))?.and_then(|category| category.owner) {
+        accrue(store, &owner, asset, amount * split.category_owner_share)?;
+    }
+    Ok(())
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// Archives the current title and content as a revision before applying the edit
+fn edit_thread(store: &mut dyn Storage, env: &Env, sender: &Addr, id: u64, title: Option<String>, content: Option<String>) -> Result<Thread, ContractError> {
+    let thread = threads().may_load(store, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    if *sender != thread.author {
+        return Err(ContractError::Unauthorized { });
+    }
+    if thread.deleted {
+        return Err(ContractError::ThreadDeleted {});
+    }
+    check_not_banned(store, env, sender, &thread.category)?;
+    let limits = CONFIG.load(store)?.content_limits;
+    if let Some(title) = &title {
+        validate_text("title", title, &limits.title, true, false)?;
+    }
+    if let Some(content) = &content {
+        validate_text("content", content, &limits.content, false, true)?;
+    }
+
+    let revision = Revision {
+        revision: thread.revision,
+        title: Some(thread.title.clone()),
+        content: thread.content.clone(),
+        editor: sender.clone(),
+        block_height: env.block.height,
+        block_time: env.block.time,
+    };
+    REVISIONS.save(store, (Target::Thread.key(), id, thread.revision), &revision)?;
+
+    let updated_thread = Thread {
+        title: title.unwrap_or(thread.title),
+        content: content.unwrap_or(thread.content),
+        updated_at: env.block.time,
+        revision: thread.revision + 1,
+        ..thread
+    };
+    threads().save(store, &id.to_be_bytes(), &updated_thread)?;
+    Ok(updated_thread)
+}
 
-    if config.comment_fee > coin_amount {
-        return Err(ContractError::LessFeeAmount {  });
+fn clear_revisions(store: &mut dyn Storage, target: Target, id: u64) -> StdResult<()> {
+    let revisions: StdResult<Vec<u32>> = REVISIONS
+    .prefix((target.key(), id))
+    .keys(store, None, None, Order::Ascending)
+    .collect();
+    for revision in revisions? {
+        REVISIONS.remove(store, (target.key(), id, revision));
+    }
+    Ok(())
+}
+
+pub fn add_comment(deps: DepsMut, env: Env, info: MessageInfo, thread_id: u64, comment: String, parent_comment_id: Option<u64>) -> Result<Response, ContractError> {  
+    let config = CONFIG.load(deps.storage)?;
+    let (charged, refund) = check_fee(&info.funds, &config.comment_fee)?;
+    let (thread, response) = save_comment(deps.storage, &env, &config.content_limits, info.sender.clone(), thread_id, comment, parent_comment_id)?;
+    for coin in &charged {
+        split_fee(deps.storage, &config, &thread, true, &FeeAsset::Native(coin.denom.clone()), coin.amount)?;
     }
-    let load_thread = threads().load(deps.storage, &thread_id.to_be_bytes().to_vec());
+    Ok(settle_fee(response, &info.sender, &charged, refund))
+}
+
+// Stores a new comment once its fee has been paid, natively or through a CW20 hook
+// Returns the parent thread along with the response
+fn save_comment(store: &mut dyn Storage, env: &Env, limits: &ContentLimits, author: Addr, thread_id: u64, comment: String, parent_comment_id: Option<u64>) -> Result<(Thread, Response), ContractError> {
+    validate_text("comment", &comment, &limits.comment, false, true)?;
+    let load_thread = threads().load(store, &thread_id.to_be_bytes());
     match load_thread {
-        Ok(_thread)=> {
-            let comment_id = next_comment_counter(deps.storage)?;
+        Ok(thread)=> {
+            if thread.deleted {
+                return Err(ContractError::ThreadDeleted {});
+            }
+            if thread.locked {
+                return Err(ContractError::ThreadLocked {});
+            }
+            check_not_banned(store, env, &author, &thread.category)?;
+            if let Some(parent_id) = parent_comment_id {
+                let parent = comments().may_load(store, &parent_id.to_be_bytes())?.ok_or(ContractError::CommentNotExists {})?;
+                if parent.thread_id != thread_id {
+                    return Err(ContractError::ParentCommentMismatch {});
+                }
+                if parent.deleted {
+                    return Err(ContractError::CommentDeleted {});
+                }
+            }
+            let comment_id = next_comment_counter(store)?;
             let new_comment = Comment {
                 comment_id,
                 comment: comment.clone(),
                 thread_id,
-                author: info.sender.clone(),
+                author: author.clone(),
+                deleted: false,
+                created_at: env.block.time,
+                created_height: env.block.height,
+                updated_at: env.block.time,
+                revision: 0,
+                parent_comment_id,
+                upvotes: 0,
+                downvotes: 0,
             };
-            comments().save(deps.storage, &comment_id.to_be_bytes().to_vec(), &new_comment)?;
-            Ok(
+            comments().save(store, &comment_id.to_be_bytes(), &new_comment)?;
+            adjust_live_count(store, &LIVE_COMMENTS, 1, 0)?;
+            Ok((
+                thread,
                 Response::new()
                 .add_attribute("method", "add_comment")
-                .add_attribute("author", info.sender)
+                .add_attribute("author", author)
                 .add_attribute("comment", comment)
                 .add_attribute("comment_id", comment_id.to_string())
-            )
+            ))
         },
         Err(_e) => Err(ContractError::ThreadNotExists {  }),
     }
 }
 
-pub fn update_comment(deps: DepsMut, info: MessageInfo, comment_id: u64, comment: String) -> Result<Response, ContractError> {  
-    comments().update(deps.storage, &comment_id.to_be_bytes(), |old| match old {
-     None => Err(ContractError::CommentNotExists { }),
-     Some(old_comment) => {
-        if info.sender != old_comment.author {
-           return Err(ContractError::Unauthorized {  });
-        }
-        let updated_comment = Comment {
-            comment: comment.clone(),
-            ..old_comment
-        };
-        Ok(updated_comment)
-     }
-    })?;
+// Fees paid in a whitelisted CW20 token, the token contract is the message sender
+pub fn receive_cw20(deps: DepsMut, env: Env, info: MessageInfo, wrapper: Cw20ReceiveMsg) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let token_fee = CW20_FEES.may_load(deps.storage, &info.sender)?.ok_or(ContractError::Cw20TokenNotAccepted {})?;
+    let author = deps.api.addr_validate(&wrapper.sender)?;
+
+    let (fee, is_comment, (thread, response)) = match from_binary(&wrapper.msg)? {
+        ReceiveMsg::CreateThread { title, content, category } => {
+            if token_fee.thread_fee > wrapper.amount {
+                return Err(ContractError::LessFeeAmount {  });
+            }
+            (token_fee.thread_fee, false, save_thread(deps.storage, &env, &config.content_limits, author.clone(), title, content, category)?)
+        },
+        ReceiveMsg::AddComment { thread_id, comment, parent_comment_id } => {
+            if token_fee.comment_fee > wrapper.amount {
+                return Err(ContractError::LessFeeAmount {  });
+            }
+            (token_fee.comment_fee, true, save_comment(deps.storage, &env, &config.content_limits, author.clone(), thread_id, comment, parent_comment_id)?)
+        },
+    };
+    split_fee(deps.storage, &config, &thread, is_comment, &FeeAsset::Cw20(info.sender.clone()), fee)?;
+
+    let refund = wrapper.amount - fee;
+    let response = response
+    .add_attribute("fee_token", info.sender.clone())
+    .add_attribute("fee_charged", fee)
+    .add_attribute("fee_refunded", refund);
+    if refund.is_zero() {
+        return Ok(response);
+    }
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: info.sender.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: author.to_string(), amount: refund })?,
+        funds: vec![],
+    });
+    Ok(response.add_message(msg))
+}
+
+pub fn update_comment(deps: DepsMut, env: Env, info: MessageInfo, comment_id: u64, comment: String) -> Result<Response, ContractError> {  
+    let old_comment = comments().may_load(deps.storage, &comment_id.to_be_bytes())?.ok_or(ContractError::CommentNotExists {})?;
+    if info.sender != old_comment.author {
+        return Err(ContractError::Unauthorized {  });
+    }
+    if old_comment.deleted {
+        return Err(ContractError::CommentDeleted {  });
+    }
+    let thread = threads().load(deps.storage, &old_comment.thread_id.to_be_bytes())?;
+    if thread.locked {
+        return Err(ContractError::ThreadLocked {});
+    }
+    check_not_banned(deps.storage, &env, &info.sender, &thread.category)?;
+    let limits = CONFIG.load(deps.storage)?.content_limits;
+    validate_text("comment", &comment, &limits.comment, false, true)?;
+
+    let revision = Revision {
+        revision: old_comment.revision,
+        title: None,
+        content: old_comment.comment.clone(),
+        editor: info.sender.clone(),
+        block_height: env.block.height,
+        block_time: env.block.time,
+    };
+    REVISIONS.save(deps.storage, (Target::Comment.key(), comment_id, old_comment.revision), &revision)?;
+
+    let updated_comment = Comment {
+        comment: comment.clone(),
+        updated_at: env.block.time,
+        revision: old_comment.revision + 1,
+        ..old_comment
+    };
+    comments().save(deps.storage, &comment_id.to_be_bytes(), &updated_comment)?;
     Ok(
         Response::new()
         .add_attribute("method", "update_comment")
         .add_attribute("author", info.sender)
+        .add_attribute("revision", updated_comment.revision.to_string())
         .add_attribute("new_comment", comment)
     )
 }
 
-pub fn update_fees(deps: DepsMut, info: MessageInfo, thread_fee: Option<Uint128>, comment_fee: Option<Uint128>) -> Result<Response, ContractError> {  
+pub fn delete_comment(deps: DepsMut, env: Env, info: MessageInfo, comment_id: u64, hard: bool) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let comment = comments().may_load(deps.storage, &comment_id.to_be_bytes())?.ok_or(ContractError::CommentNotExists {})?;
+    // A placeholder can still be hard deleted to drop its edit history
+    if comment.deleted && !hard {
+        return Err(ContractError::CommentDeleted {});
+    }
+    let was_deleted = comment.deleted;
+    let thread = threads().load(deps.storage, &comment.thread_id.to_be_bytes())?;
+    if info.sender != comment.author && info.sender != thread.author && !can_moderate(deps.storage, &config, &info.sender, &thread.category)? {
+        return Err(ContractError::Unauthorized {});
+    }
 
-  let config = CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
-        if info.sender != config.admin_addr {
-            return Err(ContractError::Unauthorized {  });
-         }
-         config.thread_fee = thread_fee.unwrap_or_else(|| config.thread_fee);
-         config.comment_fee = comment_fee.unwrap_or_else(|| config.comment_fee);
-         
-         Ok(config)
-    })?;
+    // Saving through the indexed map keeps the comment__thread entry in sync
+    let placeholder = Comment {
+        comment: String::new(),
+        deleted: true,
+        updated_at: env.block.time,
+        ..comment
+    };
+    comments().save(deps.storage, &comment_id.to_be_bytes(), &placeholder)?;
+    if !was_deleted {
+        adjust_live_count(deps.storage, &LIVE_COMMENTS, 0, 1)?;
+    }
+    // Soft delete blanks the text, hard delete also drops the earlier versions
+    if hard {
+        clear_revisions(deps.storage, Target::Comment, comment_id)?;
+    }
 
     Ok(
         Response::new()
-        .add_attribute("method", "update_fees")
-        .add_attribute("author", info.sender)
-        .add_attribute("thread_fee", config.thread_fee)
-        .add_attribute("comment_fee", config.comment_fee),
+        .add_attribute("method", "delete_comment")
+        .add_attribute("sender", info.sender)
+        .add_attribute("comment_id", comment_id.to_string())
+        .add_attribute("hard", hard.to_string())
     )
 }
 
+pub fn vote(deps: DepsMut, env: Env, info: MessageInfo, target: Target, id: u64, direction: Option<VoteDirection>) -> Result<Response, ContractError> {
+    let previous = VOTES.may_load(deps.storage, (target.key(), id, &info.sender))?;
 
-fn send(deps: DepsMut, env: Env, info: MessageInfo, address: Addr, amount: Uint128) -> Result<Response, ContractError> {  
-    
-    let config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != config.admin_addr {
-        return Err(ContractError::Unauthorized { });
-    }
-    let balance = deps.querier.query_balance(env.contract.address.clone(), "uluna".to_string())?;
-    
-    if amount > balance.amount {
-        return Err(ContractError::NotEnoughBalance { });
+    match target {
+        Target::Thread => {
+            let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+            if thread.deleted {
+                return Err(ContractError::ThreadDeleted {});
+            }
+            check_not_banned(deps.storage, &env, &info.sender, &thread.category)?;
+            let (upvotes, downvotes) = tally_vote(thread.upvotes, thread.downvotes, previous, direction);
+            threads().save(deps.storage, &id.to_be_bytes(), &Thread { upvotes, downvotes, ..thread })?;
+        },
+        Target::Comment => {
+            let comment = comments().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::CommentNotExists {})?;
+            if comment.deleted {
+                return Err(ContractError::CommentDeleted {});
+            }
+            let thread = threads().load(deps.storage, &comment.thread_id.to_be_bytes())?;
+            check_not_banned(deps.storage, &env, &info.sender, &thread.category)?;
+            let (upvotes, downvotes) = tally_vote(comment.upvotes, comment.downvotes, previous, direction);
+            comments().save(deps.storage, &id.to_be_bytes(), &Comment { upvotes, downvotes, ..comment })?;
+        },
     }
 
-    let msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: address.to_string(),
-        amount: vec![
-            Coin {
-                denom: "uluna".to_string(),
-                amount: amount,
-            },
-        ],
-    });
+    match direction {
+        Some(direction) => VOTES.save(deps.storage, (target.key(), id, &info.sender), &direction)?,
+        None => VOTES.remove(deps.storage, (target.key(), id, &info.sender)),
+    }
 
-    Ok(Response::new().add_message(msg))
+    Ok(
+        Response::new()
+        .add_attribute("method", "vote")
+        .add_attribute("voter", info.sender)
+        .add_attribute("target", target.key())
+        .add_attribute("id", id.to_string())
+        .add_attribute("direction", direction.map_or("none", |d| d.as_str())),
+    )
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetThreadById { id } => to_binary(&query_thread_by_id(deps, id)?),
-        QueryMsg::GetThreadsByCategory {category, offset, limit} => to_binary(&query_threads_by_category(deps, category, offset, limit)?),
-        QueryMsg::GetThreadsByAuthor { author, offset, limit } =>  to_binary(&query_threads_by_author(deps, author, offset, limit)?),
-        QueryMsg::GetCommentById {id} => to_binary(&query_comment_by_id(deps, id)?),
-        QueryMsg::GetCommentsByThread { thread_id, offset, limit } => to_binary(&query_comments_by_thread(deps, thread_id, offset, limit)?),
-        QueryMsg::GetConfig {  } => to_binary(&query_config(deps)?)
+// Takes the previous vote out of the totals and adds the new one
+fn tally_vote(mut upvotes: u64, mut downvotes: u64, previous: Option<VoteDirection>, direction: Option<VoteDirection>) -> (u64, u64) {
+    match previous {
+        Some(VoteDirection::Up) => upvotes -= 1,
+        Some(VoteDirection::Down) => downvotes -= 1,
+        None => {},
     }
+    match direction {
+        Some(VoteDirection::Up) => upvotes += 1,
+        Some(VoteDirection::Down) => downvotes += 1,
+        None => {},
+    }
+    (upvotes, downvotes)
 }
 
-fn query_thread_by_id(deps: Deps, id: u64) -> StdResult<Thread> {
-    let thread = threads().load(deps.storage, &id.to_be_bytes().to_vec())?;
-    Ok(Thread { id:thread.id, title: thread.title, content: thread.content, author: thread.author, category: thread.category})
-}
-
-// Limits for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
+pub fn react(deps: DepsMut, env: Env, info: MessageInfo, target: Target, id: u64, reaction: String) -> Result<Response, ContractError> {
+    let (_, category) = target_author(deps.storage, target, id)?;
+    check_not_banned(deps.storage, &env, &info.sender, &category)?;
 
-fn query_threads_by_category(deps: Deps, category: String, offset: Option<u64>, limit: Option<u32>) -> StdResult<ThreadsResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let latest_thread_id: u64 = THREAD_COUNTER.may_load(deps.storage)?.unwrap_or_default();
-    let finish = offset.map(|offset| Bound::inclusive((latest_thread_id - offset).to_be_bytes().to_vec()));
-   
-    let list: StdResult<Vec<_>>  = threads()
-    .idx.category
-    .prefix(category)
-    .range(deps.storage, None, finish, Order::Descending)
-    .take(limit)
-    .map(|item| item.map(|(_, t)| t))
-    .collect();
+    let mut applied = REACTIONS.may_load(deps.storage, (target.key(), id, &info.sender))?.unwrap_or_default();
+    let count = REACTION_COUNTS.may_load(deps.storage, (target.key(), id, &reaction))?.unwrap_or_default();
 
-    let result = ThreadsResponse {
-        entries: list?,
+    // Reactions removed from the allowlist can still be taken back
+    let added = !applied.contains(&reaction);
+    let count = if added {
+        let allowed = ALLOWED_REACTIONS.may_load(deps.storage)?.unwrap_or_default();
+        if !allowed.contains(&reaction) {
+            return Err(ContractError::ReactionNotAllowed {});
+        }
+        applied.push(reaction.clone());
+        count + 1
+    } else {
+        applied.retain(|r| *r != reaction);
+        count - 1
     };
-    Ok(result)    
-}
-
-fn query_threads_by_author(deps: Deps, author: Addr, offset: Option<u64>, limit: Option<u32>) -> StdResult<ThreadsResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let latest_thread_id: u64 = THREAD_COUNTER.may_load(deps.storage)?.unwrap_or_default();
-    let finish = offset.map(|offset| Bound::inclusive((latest_thread_id - offset).to_be_bytes().to_vec()));
 
-    let list: StdResult<Vec<_>>  = threads()
-    .idx.author
-    .prefix(author)
-    .range(deps.storage, None, finish, Order::Descending)
-    .take(limit)
-    .map(|item| item.map(|(_, t)| t))
-    .collect();
+    if applied.is_empty() {
+        REACTIONS.remove(deps.storage, (target.key(), id, &info.sender));
+    } else {
+        REACTIONS.save(deps.storage, (target.key(), id, &info.sender), &applied)?;
+    }
+    if count == 0 {
+        REACTION_COUNTS.remove(deps.storage, (target.key(), id, &reaction));
+    } else {
+        REACTION_COUNTS.save(deps.storage, (target.key(), id, &reaction), &count)?;
+    }
 
-    let result = ThreadsResponse {
-        entries: list?,
-    };
-    Ok(result)    
+    Ok(
+        Response::new()
+        .add_attribute("method", "react")
+        .add_attribute("reactor", info.sender)
+        .add_attribute("target", target.key())
+        .add_attribute("id", id.to_string())
+        .add_attribute("reaction", reaction)
+        .add_attribute("added", added.to_string()),
+    )
 }
 
-fn query_comment_by_id(deps: Deps, comment_id: u64) -> StdResult<Comment> {
-    let comment = comments().load(deps.storage, &comment_id.to_be_bytes().to_vec())?;
-    Ok(comment)
-}
+pub fn update_allowed_reactions(deps: DepsMut, info: MessageInfo, reactions: Vec<String>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
 
-fn query_comments_by_thread(deps: Deps, thread_id: u64, offset: Option<u64>, limit: Option<u32>) -> StdResult<CommentsResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let latest_comment_id: u64 = COMMENT_COUNTER.may_load(deps.storage)?.unwrap_or_default();
-    let finish = offset.map(|offset| Bound::inclusive((latest_comment_id - offset).to_be_bytes().to_vec()));
+    for (i, reaction) in reactions.iter().enumerate() {
+        if reaction.trim().is_empty() || reactions[..i].contains(reaction) {
+            return Err(ContractError::InvalidReaction { reaction: reaction.clone() });
+        }
+    }
+    ALLOWED_REACTIONS.save(deps.storage, &reactions)?;
 
-    let list: StdResult<Vec<_>>  = comments()
-    .idx.thread
-    .prefix(thread_id.to_be_bytes().to_vec())
-    .range(deps.storage, None, finish, Order::Descending)
-    .take(limit)
-    .map(|item| item.map(|(_, comment)| comment))
-    .collect();
-    let result = CommentsResponse {
-        entries: list?,
-    };
-    Ok(result)    
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_allowed_reactions")
+        .add_attribute("reactions", reactions.join(",")),
+    )
 }
 
-fn query_config(deps: Deps) -> StdResult<Config> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(config)
+// Loads the author and category of a thread or comment that has not been deleted,
+// comments of deleted threads count as deleted
+fn target_author(store: &dyn Storage, target: Target, id: u64) -> Result<(Addr, String), ContractError> {
+    match target {
+        Target::Thread => {
+            let thread = threads().may_load(store, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+            if thread.deleted {
+                return Err(ContractError::ThreadDeleted {});
+            }
+            Ok((thread.author, thread.category))
+        },
+        Target::Comment => {
+            let comment = comments().may_load(store, &id.to_be_bytes())?.ok_or(ContractError::CommentNotExists {})?;
+            if comment.deleted {
+                return Err(ContractError::CommentDeleted {});
+            }
+            let thread = threads().load(store, &comment.thread_id.to_be_bytes())?;
+            if thread.deleted {
+                return Err(ContractError::ThreadDeleted {});
+            }
+            Ok((comment.author, thread.category))
+        },
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info, MockQuerier, MockApi};
-    use cosmwasm_std::{coins, from_binary, OwnedDeps, MemoryStorage};
+pub fn tip(deps: DepsMut, info: MessageInfo, target: Target, id: u64) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let (author, _) = target_author(deps.storage, target, id)?;
+    if info.funds.iter().all(|c| c.amount.is_zero()) {
+        return Err(ContractError::EmptyTip {});
+    }
 
-    fn instantiate_contract() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
-        let msg = InstantiateMsg { thread_fee: Option::Some(Uint128::from(10000u128)), comment_fee: Option::Some(Uint128::from(10000u128))};
-        let info = mock_info("creator", &coins(1000000, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        return deps;
+    let mut payout: Vec<Coin> = vec![];
+    for coin in info.funds.iter().filter(|c| !c.amount.is_zero()) {
+        let total = TIP_TOTALS.may_load(deps.storage, (target.key(), id, &coin.denom))?.unwrap_or_default();
+        TIP_TOTALS.save(deps.storage, (target.key(), id, &coin.denom), &(total + coin.amount))?;
+        let earned = AUTHOR_TIPS.may_load(deps.storage, (&author, &coin.denom))?.unwrap_or_default();
+        AUTHOR_TIPS.save(deps.storage, (&author, &coin.denom), &(earned + coin.amount))?;
+
+        let amount = coin.amount - coin.amount * config.tip_cut;
+        if !amount.is_zero() {
+            payout.push(Coin { denom: coin.denom.clone(), amount });
+        }
     }
 
-    fn create_new_thread(deps: DepsMut) {
-        let info = mock_info("creator", &coins(10000000, "uluna"));
-        let title = String::from("First Thread");
-        let content = String::from("First Message");
-        let category = String::from("General");
-        let msg = ExecuteMsg::CreateThread { title: title.clone(), content: content.clone(), category: category.clone()};
-        let _res = execute(deps, mock_env(), info, msg);
+    let mut response = Response::new()
+    .add_attribute("method", "tip")
+    .add_attribute("tipper", info.sender)
+    .add_attribute("author", author.clone())
+    .add_attribute("target", target.key())
+    .add_attribute("id", id.to_string());
+    if !payout.is_empty() {
+        response = response.add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: author.to_string(),
+            amount: payout,
+        }));
+    }
+    Ok(response)
+}
+
+pub fn update_fees(deps: DepsMut, info: MessageInfo, thread_fee: Option<Vec<Coin>>, comment_fee: Option<Vec<Coin>>, tip_cut: Option<Decimal>) -> Result<Response, ContractError> {  
+
+  let config = CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        if !is_admin(&config, &info.sender) {
+            return Err(ContractError::Unauthorized {  });
+         }
+         config.thread_fee = thread_fee.unwrap_or(config.thread_fee);
+         config.comment_fee = comment_fee.unwrap_or(config.comment_fee);
+         config.tip_cut = tip_cut.unwrap_or(config.tip_cut);
+         if config.tip_cut > Decimal::one() {
+            return Err(ContractError::InvalidTipCut {});
+         }
+         validate_fee_schedule(&config.thread_fee)?;
+         validate_fee_schedule(&config.comment_fee)?;
+         
+         Ok(config)
+    })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_fees")
+        .add_attribute("author", info.sender)
+        .add_attribute("thread_fee", coins_to_string(&config.thread_fee))
+        .add_attribute("comment_fee", coins_to_string(&config.comment_fee))
+        .add_attribute("tip_cut", config.tip_cut.to_string()),
+    )
+}
+
+pub fn delete_thread(deps: DepsMut, env: Env, info: MessageInfo, id: u64, hard: bool) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    if info.sender != thread.author && !can_moderate(deps.storage, &config, &info.sender, &thread.category)? {
+        return Err(ContractError::Unauthorized { });
+    }
+    // A soft deleted thread can still be hard deleted to wipe its text
+    let wiped = thread.title.is_empty() && thread.content.is_empty();
+    if thread.deleted && (!hard || wiped) {
+        return Err(ContractError::ThreadDeleted {});
+    }
+    let was_deleted = thread.deleted;
+
+    // Hard delete wipes the text, soft delete only hides the thread
+    let (title, content) = if hard { (String::new(), String::new()) } else { (thread.title, thread.content) };
+    // Deleted threads give up their pin slots
+    PINS.remove(deps.storage, (GLOBAL_PIN_SCOPE, id));
+    PINS.remove(deps.storage, (&category_key(&thread.category), id));
+    let thread = Thread {
+        title,
+        content,
+        deleted: true,
+        updated_at: env.block.time,
+        pinned: false,
+        ..thread
+    };
+    threads().save(deps.storage, &id.to_be_bytes(), &thread)?;
+    if !was_deleted {
+        adjust_live_count(deps.storage, &LIVE_THREADS, 0, 1)?;
+    }
+    if hard {
+        clear_revisions(deps.storage, Target::Thread, id)?;
+    }
+
+    // The cascade removes one batch now, PurgeComments takes care of the rest
+    let (removed_comments, more_comments) = if config.comment_cascade == CascadePolicy::Delete {
+        purge_comments(deps.storage, id, CASCADE_BATCH)?
+    } else {
+        (0, false)
+    };
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "delete_thread")
+        .add_attribute("sender", info.sender)
+        .add_attribute("thread_id", id.to_string())
+        .add_attribute("hard", hard.to_string())
+        .add_attribute("removed_comments", removed_comments.to_string())
+        .add_attribute("more_comments", more_comments.to_string()),
+    )
+}
+
+// Removes up to limit comments of a thread together with their revisions, reaction counts
+// and tip totals. Per-voter VOTES and REACTIONS entries are kept on purpose: their number
+// is unbounded and they are inert once the comment is gone.
+// Returns the number removed and whether comments are left.
+fn purge_comments(store: &mut dyn Storage, thread_id: u64, limit: u32) -> StdResult<(u64, bool)> {
+    let comment_keys = comments()
+    .idx.thread
+    .prefix(thread_id.to_be_bytes().to_vec())
+    .keys(store, None, None, Order::Ascending)
+    .take(limit as usize + 1)
+    .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    let more = comment_keys.len() > limit as usize;
+
+    let mut removed: u64 = 0;
+    for key in comment_keys.into_iter().take(limit as usize) {
+        let comment = comments().load(store, &key)?;
+        let target = (Target::Comment.key(), comment.comment_id);
+        clear_revisions(store, Target::Comment, comment.comment_id)?;
+        let reactions = REACTION_COUNTS.prefix(target).keys(store, None, None, Order::Ascending).collect::<StdResult<Vec<String>>>()?;
+        for reaction in reactions {
+            REACTION_COUNTS.remove(store, (target.0, target.1, &reaction));
+        }
+        let denoms = TIP_TOTALS.prefix(target).keys(store, None, None, Order::Ascending).collect::<StdResult<Vec<String>>>()?;
+        for denom in denoms {
+            TIP_TOTALS.remove(store, (target.0, target.1, &denom));
+        }
+        comments().remove(store, &key)?;
+        if !comment.deleted {
+            adjust_live_count(store, &LIVE_COMMENTS, 0, 1)?;
+        }
+        removed += 1;
+    }
+    Ok((removed, more))
+}
+
+// Anyone can continue the delete cascade of a deleted thread
+pub fn purge_thread_comments(deps: DepsMut, info: MessageInfo, thread_id: u64, limit: Option<u32>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let thread = threads().may_load(deps.storage, &thread_id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    if !thread.deleted || config.comment_cascade != CascadePolicy::Delete {
+        return Err(ContractError::NothingToPurge {});
+    }
+    let (removed_comments, more_comments) = purge_comments(deps.storage, thread_id, limit.unwrap_or(CASCADE_BATCH).min(CASCADE_BATCH))?;
+    if removed_comments == 0 {
+        return Err(ContractError::NothingToPurge {});
+    }
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "purge_comments")
+        .add_attribute("sender", info.sender)
+        .add_attribute("thread_id", thread_id.to_string())
+        .add_attribute("removed_comments", removed_comments.to_string())
+        .add_attribute("more_comments", more_comments.to_string()),
+    )
+}
+
+pub fn update_cascade_policy(deps: DepsMut, info: MessageInfo, comment_cascade: CascadePolicy) -> Result<Response, ContractError> {
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        if !is_admin(&config, &info.sender) {
+            return Err(ContractError::Unauthorized {  });
+        }
+        config.comment_cascade = comment_cascade.clone();
+        Ok(config)
+    })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_cascade_policy")
+        .add_attribute("author", info.sender)
+        .add_attribute("comment_cascade", format!("{:?}", comment_cascade)),
+    )
+}
+pub fn move_thread(deps: DepsMut, env: Env, info: MessageInfo, id: u64, category: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let category = open_category(deps.storage, &category)?;
+    let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    // Scoped moderators need both the source and the destination category
+    if !can_moderate(deps.storage, &config, &info.sender, &thread.category)? || !can_moderate(deps.storage, &config, &info.sender, &category)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if thread.deleted {
+        return Err(ContractError::ThreadDeleted {});
+    }
+
+    // A category pin does not follow the thread into another category
+    PINS.remove(deps.storage, (&category_key(&thread.category), id));
+    let pinned = PINS.has(deps.storage, (GLOBAL_PIN_SCOPE, id));
+
+    // Saving through the indexed map moves the threads__category entry as well
+    let thread = Thread {
+        category: category.clone(),
+        updated_at: env.block.time,
+        pinned,
+        ..thread
+    };
+    threads().save(deps.storage, &id.to_be_bytes(), &thread)?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "move_thread")
+        .add_attribute("moderator", info.sender)
+        .add_attribute("thread_id", id.to_string())
+        .add_attribute("category", category),
+    )
+}
+
+// Expired bans and bans scoped to another category are ignored
+fn check_not_banned(store: &dyn Storage, env: &Env, address: &Addr, category: &str) -> Result<(), ContractError> {
+    let ban = match BANS.may_load(store, address)? {
+        Some(ban) => ban,
+        None => return Ok(()),
+    };
+    if ban.expires.is_expired(&env.block) {
+        return Ok(());
+    }
+    if ban.category.as_ref().is_some_and(|scope| category_key(scope) != category_key(category)) {
+        return Ok(());
+    }
+    Err(ContractError::Banned { reason: ban.reason })
+}
+
+// Moderators ban within the categories they moderate, bans everywhere need unscoped rights
+fn can_moderate_ban(store: &dyn Storage, config: &Config, sender: &Addr, category: &Option<String>) -> StdResult<bool> {
+    match category {
+        Some(category) => can_moderate(store, config, sender, category),
+        None => can_moderate_all(store, config, sender),
+    }
+}
+
+// Nobody can ban or unban themselves, and only the admin can ban or unban a moderator
+fn can_ban_target(store: &dyn Storage, config: &Config, sender: &Addr, target: &Addr) -> StdResult<bool> {
+    if sender == target {
+        return Ok(false);
+    }
+    Ok(is_admin(config, sender) || MODERATORS.may_load(store, target)?.is_none())
+}
+
+pub fn ban(deps: DepsMut, env: Env, info: MessageInfo, address: Addr, category: Option<String>, expires: Option<Expiration>, reason: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(address.as_str())?;
+    let category = category.map(|category| registered_category(deps.storage, &category)).transpose()?;
+    if !can_moderate_ban(deps.storage, &config, &info.sender, &category)? || !can_ban_target(deps.storage, &config, &info.sender, &address)? || is_admin(&config, &address) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidBanExpiry {});
+    }
+    validate_text("reason", &reason, &config.content_limits.reason, false, true)?;
+
+    // A new ban replaces the previous one of the address, so a ban still in force
+    // can only be changed by someone who could lift it
+    if let Some(existing) = BANS.may_load(deps.storage, &address)? {
+        if !existing.expires.is_expired(&env.block) && !can_moderate_ban(deps.storage, &config, &info.sender, &existing.category)? {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+    let ban = Ban { address: address.clone(), category, expires, reason, banned_by: info.sender.clone() };
+    BANS.save(deps.storage, &address, &ban)?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "ban")
+        .add_attribute("moderator", info.sender)
+        .add_attribute("address", address)
+        .add_attribute("category", ban.category.unwrap_or_default())
+        .add_attribute("expires", ban.expires.to_string()),
+    )
+}
+
+pub fn unban(deps: DepsMut, info: MessageInfo, address: Addr) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let ban = BANS.may_load(deps.storage, &address)?.ok_or(ContractError::NotBanned {})?;
+    if !can_moderate_ban(deps.storage, &config, &info.sender, &ban.category)? || !can_ban_target(deps.storage, &config, &info.sender, &address)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    BANS.remove(deps.storage, &address);
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "unban")
+        .add_attribute("moderator", info.sender)
+        .add_attribute("address", address),
+    )
+}
+
+// The author, moderators of the category and the admin can lock and unlock
+pub fn set_thread_lock(deps: DepsMut, env: Env, info: MessageInfo, id: u64, locked: bool) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    // The author may only lift a lock they set themselves
+    let author_lock = !locked && thread.locked_by.as_ref().is_some_and(|locker| *locker == thread.author);
+    let author_allowed = info.sender == thread.author && (locked || author_lock);
+    if !author_allowed && !can_moderate(deps.storage, &config, &info.sender, &thread.category)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if thread.deleted {
+        return Err(ContractError::ThreadDeleted {});
+    }
+    match (thread.locked, locked) {
+        (true, true) => return Err(ContractError::ThreadLocked {}),
+        (false, false) => return Err(ContractError::ThreadNotLocked {}),
+        _ => {},
+    }
+
+    let locked_by = if locked { Some(info.sender.clone()) } else { None };
+    threads().save(deps.storage, &id.to_be_bytes(), &Thread { locked, locked_by, updated_at: env.block.time, ..thread })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", if locked { "lock_thread" } else { "unlock_thread" })
+        .add_attribute("sender", info.sender)
+        .add_attribute("thread_id", id.to_string()),
+    )
+}
+
+// Category pins need moderation rights on the category, global pins on every category
+fn pin_scope_key(store: &dyn Storage, config: &Config, sender: &Addr, thread: &Thread, scope: PinScope) -> Result<String, ContractError> {
+    let (allowed, key) = match scope {
+        PinScope::Category => (can_moderate(store, config, sender, &thread.category)?, category_key(&thread.category)),
+        PinScope::Global => (can_moderate_all(store, config, sender)?, GLOBAL_PIN_SCOPE.to_string()),
+    };
+    if !allowed {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(key)
+}
+
+pub fn pin_thread(deps: DepsMut, env: Env, info: MessageInfo, id: u64, scope: PinScope) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    let key = pin_scope_key(deps.storage, &config, &info.sender, &thread, scope)?;
+    if thread.deleted {
+        return Err(ContractError::ThreadDeleted {});
+    }
+    if PINS.has(deps.storage, (&key, id)) {
+        return Err(ContractError::ThreadAlreadyPinned {});
+    }
+    let pins = PINS.prefix(&key).keys(deps.storage, None, None, Order::Ascending).count();
+    if pins >= MAX_PINS {
+        return Err(ContractError::TooManyPins { max: MAX_PINS as u32 });
+    }
+
+    PINS.save(deps.storage, (&key, id), &env.block.time)?;
+    threads().save(deps.storage, &id.to_be_bytes(), &Thread { pinned: true, ..thread })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "pin_thread")
+        .add_attribute("moderator", info.sender)
+        .add_attribute("thread_id", id.to_string())
+        .add_attribute("scope", if key == GLOBAL_PIN_SCOPE { "global" } else { "category" }),
+    )
+}
+
+pub fn unpin_thread(deps: DepsMut, info: MessageInfo, id: u64, scope: PinScope) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let thread = threads().may_load(deps.storage, &id.to_be_bytes())?.ok_or(ContractError::ThreadNotExists {})?;
+    let key = pin_scope_key(deps.storage, &config, &info.sender, &thread, scope)?;
+    if !PINS.has(deps.storage, (&key, id)) {
+        return Err(ContractError::ThreadNotPinned {});
+    }
+
+    PINS.remove(deps.storage, (&key, id));
+    // Still pinned if the other scope holds a pin
+    let pinned = PINS.has(deps.storage, (GLOBAL_PIN_SCOPE, id)) || PINS.has(deps.storage, (&category_key(&thread.category), id));
+    threads().save(deps.storage, &id.to_be_bytes(), &Thread { pinned, ..thread })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "unpin_thread")
+        .add_attribute("moderator", info.sender)
+        .add_attribute("thread_id", id.to_string())
+        .add_attribute("scope", if key == GLOBAL_PIN_SCOPE { "global" } else { "category" }),
+    )
+}
+
+pub fn add_moderator(deps: DepsMut, info: MessageInfo, address: Addr, categories: Option<Vec<String>>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Adding an existing moderator replaces their scopes
+    let address = deps.api.addr_validate(address.as_str())?;
+    clear_moderator_scopes(deps.storage, &address)?;
+    MODERATORS.save(deps.storage, &address, &Moderator { address: address.clone(), scoped: categories.is_some() })?;
+    let categories = categories
+    .unwrap_or_default()
+    .iter()
+    .map(|category| registered_category(deps.storage, category))
+    .collect::<Result<Vec<_>, _>>()?;
+    for category in categories.iter() {
+        let scope = ModeratorScope { moderator: address.clone(), category: category.clone() };
+        moderator_scopes().save(deps.storage, (address.clone(), category.clone()), &scope)?;
+    }
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "add_moderator")
+        .add_attribute("moderator", address)
+        .add_attribute("categories", categories.join(",")),
+    )
+}
+
+pub fn remove_moderator(deps: DepsMut, info: MessageInfo, address: Addr) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !MODERATORS.has(deps.storage, &address) {
+        return Err(ContractError::ModeratorNotExists {});
+    }
+
+    clear_moderator_scopes(deps.storage, &address)?;
+    MODERATORS.remove(deps.storage, &address);
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "remove_moderator")
+        .add_attribute("moderator", address),
+    )
+}
+
+fn clear_moderator_scopes(store: &mut dyn Storage, address: &Addr) -> StdResult<()> {
+    let categories: StdResult<Vec<String>> = moderator_scopes()
+    .prefix(address.clone())
+    .keys(store, None, None, Order::Ascending)
+    .collect();
+    for category in categories? {
+        moderator_scopes().remove(store, (address.clone(), category))?;
+    }
+    Ok(())
+}
+
+pub fn create_category(deps: DepsMut, info: MessageInfo, name: String, description: Option<String>, sort_order: Option<u32>, owner: Option<Addr>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = category_key(&name);
+    if key.is_empty() {
+        return Err(ContractError::InvalidCategoryName {});
+    }
+    validate_text("category", name.trim(), &config.content_limits.category, false, false)?;
+    if CATEGORIES.has(deps.storage, &key) {
+        return Err(ContractError::CategoryExists {});
+    }
+
+    let category = Category {
+        name: name.trim().to_string(),
+        description: description.unwrap_or_default(),
+        sort_order: sort_order.unwrap_or_default(),
+        archived: false,
+        owner: owner.map(|owner| deps.api.addr_validate(owner.as_str())).transpose()?,
+    };
+    CATEGORIES.save(deps.storage, &key, &category)?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "create_category")
+        .add_attribute("category", category.name),
+    )
+}
+
+pub fn update_category(deps: DepsMut, info: MessageInfo, name: String, description: Option<String>, sort_order: Option<u32>, archived: Option<bool>, owner: Option<Option<Addr>>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    // Some(None) removes the owner, None leaves it unchanged
+    let owner = match owner {
+        Some(Some(owner)) => Some(Some(deps.api.addr_validate(owner.as_str())?)),
+        owner => owner,
+    };
+
+    let category = CATEGORIES.update(deps.storage, &category_key(&name), |old| match old {
+        Some(mut category) => {
+            category.description = description.unwrap_or(category.description);
+            category.sort_order = sort_order.unwrap_or(category.sort_order);
+            category.archived = archived.unwrap_or(category.archived);
+            category.owner = owner.unwrap_or(category.owner);
+            Ok(category)
+        },
+        None => Err(ContractError::CategoryNotExists {}),
+    })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_category")
+        .add_attribute("category", category.name)
+        .add_attribute("archived", category.archived.to_string()),
+    )
+}
+
+// Returns the registered name of a category that accepts new threads
+fn open_category(store: &dyn Storage, name: &str) -> Result<String, ContractError> {
+    let category = CATEGORIES.may_load(store, &category_key(name))?.ok_or(ContractError::CategoryNotExists {})?;
+    if category.archived {
+        return Err(ContractError::CategoryArchived {});
+    }
+    Ok(category.name)
+}
+
+fn registered_category(store: &dyn Storage, name: &str) -> Result<String, ContractError> {
+    let category = CATEGORIES.may_load(store, &category_key(name))?.ok_or(ContractError::CategoryNotExists {})?;
+    Ok(category.name)
+}
+
+// Falls back to the raw name for threads created before the registry existed
+fn resolve_category(store: &dyn Storage, name: String) -> StdResult<String> {
+    Ok(CATEGORIES.may_load(store, &category_key(&name))?.map(|category| category.name).unwrap_or(name))
+}
+
+// Always false once the admin role has been renounced
+fn is_admin(config: &Config, address: &Addr) -> bool {
+    config.admin_addr.as_ref() == Some(address)
+}
+
+// Admin and unscoped moderators can moderate every category
+fn can_moderate_all(store: &dyn Storage, config: &Config, address: &Addr) -> StdResult<bool> {
+    if is_admin(config, address) {
+        return Ok(true);
+    }
+    Ok(MODERATORS.may_load(store, address)?.is_some_and(|moderator| !moderator.scoped))
+}
+
+fn can_moderate(store: &dyn Storage, config: &Config, address: &Addr, category: &str) -> StdResult<bool> {
+    if is_admin(config, address) {
+        return Ok(true);
+    }
+    match MODERATORS.may_load(store, address)? {
+        Some(moderator) if moderator.scoped => Ok(moderator_scopes().may_load(store, (address.clone(), category.to_string()))?.is_some()),
+        Some(_) => Ok(true),
+        None => Ok(false),
+    }
+}
+
+fn send(deps: DepsMut, env: Env, info: MessageInfo, address: Addr, amount: Uint128, denom: String) -> Result<Response, ContractError> {  
+    
+    let config = CONFIG.load(deps.storage)?;
+    
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized { });
+    }
+    let balance = deps.querier.query_balance(env.contract.address.clone(), denom.clone())?;
+    // Accrued rewards belong to their recipients
+    let reserved = TOTAL_CLAIMABLE.may_load(deps.storage, &denom)?.unwrap_or_default();
+    
+    if amount.checked_add(reserved).map_err(|_| ContractError::NotEnoughBalance {})? > balance.amount {
+        return Err(ContractError::NotEnoughBalance { });
+    }
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: address.to_string(),
+        amount: vec![
+            Coin {
+                denom,
+                amount,
+            },
+        ],
+    });
+
+    Ok(Response::new().add_message(msg))
+}
+
+pub fn update_fee_split(deps: DepsMut, info: MessageInfo, fee_split: FeeSplit) -> Result<Response, ContractError> {
+    let treasury = fee_split.treasury.as_ref().map(|treasury| deps.api.addr_validate(treasury.as_str())).transpose()?;
+    let config = CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        if !is_admin(&config, &info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        if fee_split.treasury_share + fee_split.thread_author_share + fee_split.category_owner_share > Decimal::one() {
+            return Err(ContractError::InvalidFeeSplit {});
+        }
+        config.fee_split = FeeSplit { treasury, ..fee_split };
+        Ok(config)
+    })?;
+
+    let split = config.fee_split;
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_fee_split")
+        .add_attribute("treasury", split.treasury.map(|treasury| treasury.to_string()).unwrap_or_default())
+        .add_attribute("treasury_share", split.treasury_share.to_string())
+        .add_attribute("thread_author_share", split.thread_author_share.to_string())
+        .add_attribute("category_owner_share", split.category_owner_share.to_string()),
+    )
+}
+
+// Pays out every accrued native and CW20 balance of the sender
+pub fn claim_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let native = CLAIMABLE
+    .prefix(&info.sender)
+    .range(deps.storage, None, None, Order::Ascending)
+    .collect::<StdResult<Vec<_>>>()?;
+    let tokens = CLAIMABLE_CW20
+    .prefix(&info.sender)
+    .range(deps.storage, None, None, Order::Ascending)
+    .collect::<StdResult<Vec<_>>>()?;
+    if native.is_empty() && tokens.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let mut response = Response::new()
+    .add_attribute("method", "claim_rewards")
+    .add_attribute("address", info.sender.clone());
+
+    let mut payout: Vec<Coin> = vec![];
+    for (denom, amount) in native {
+        CLAIMABLE.remove(deps.storage, (&info.sender, &denom));
+        TOTAL_CLAIMABLE.update(deps.storage, &denom, |total| -> StdResult<_> { Ok(total.unwrap_or_default().checked_sub(amount)?) })?;
+        payout.push(Coin { denom, amount });
+    }
+    if !payout.is_empty() {
+        response = response
+        .add_attribute("claimed", coins_to_string(&payout))
+        .add_message(BankMsg::Send { to_address: info.sender.to_string(), amount: payout });
+    }
+
+    for (token, amount) in tokens {
+        CLAIMABLE_CW20.remove(deps.storage, (&info.sender, &token));
+        TOTAL_CLAIMABLE_CW20.update(deps.storage, &token, |total| -> StdResult<_> { Ok(total.unwrap_or_default().checked_sub(amount)?) })?;
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: info.sender.to_string(), amount })?,
+            funds: vec![],
+        });
+    }
+
+    Ok(response)
+}
+
+// Admin rotation is two-step, the proposed address has to accept before it takes over
+pub fn propose_new_admin(deps: DepsMut, env: Env, info: MessageInfo, address: Addr, expires: Option<Expiration>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if expires.is_some_and(|expires| expires.is_expired(&env.block)) {
+        return Err(ContractError::AdminTransferExpired {});
+    }
+
+    let pending = PendingAdmin { address: deps.api.addr_validate(address.as_str())?, expires };
+    PENDING_ADMIN.save(deps.storage, &pending)?;
+
+    let event = Event::new("admin_transfer_proposed")
+    .add_attribute("admin", info.sender)
+    .add_attribute("pending_admin", pending.address)
+    .add_attribute("expires", expires.unwrap_or_default().to_string());
+    Ok(Response::new().add_attribute("method", "propose_new_admin").add_event(event))
+}
+
+pub fn accept_admin(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending = PENDING_ADMIN.may_load(deps.storage)?.ok_or(ContractError::NoPendingAdmin {})?;
+    if info.sender != pending.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if pending.expires.is_some_and(|expires| expires.is_expired(&env.block)) {
+        return Err(ContractError::AdminTransferExpired {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let previous_admin = config.admin_addr.clone();
+    CONFIG.save(deps.storage, &Config { admin_addr: Some(pending.address.clone()), ..config })?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    let event = Event::new("admin_transfer_accepted")
+    .add_attribute("previous_admin", previous_admin.map(|admin| admin.to_string()).unwrap_or_default())
+    .add_attribute("admin", pending.address);
+    Ok(Response::new().add_attribute("method", "accept_admin").add_event(event))
+}
+
+pub fn cancel_admin_transfer(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pending = PENDING_ADMIN.may_load(deps.storage)?.ok_or(ContractError::NoPendingAdmin {})?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    let event = Event::new("admin_transfer_cancelled")
+    .add_attribute("admin", info.sender)
+    .add_attribute("pending_admin", pending.address);
+    Ok(Response::new().add_attribute("method", "cancel_admin_transfer").add_event(event))
+}
+
+// Leaves the contract without an admin, fees and categories are frozen from then on
+pub fn renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    CONFIG.save(deps.storage, &Config { admin_addr: None, ..config })?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    let event = Event::new("admin_renounced").add_attribute("previous_admin", info.sender);
+    Ok(Response::new().add_attribute("method", "renounce_admin").add_event(event))
+}
+
+pub fn update_content_limits(deps: DepsMut, info: MessageInfo, content_limits: ContentLimits) -> Result<Response, ContractError> {
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        if !is_admin(&config, &info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        let fields = [
+            ("title", &content_limits.title),
+            ("content", &content_limits.content),
+            ("category", &content_limits.category),
+            ("comment", &content_limits.comment),
+            ("reason", &content_limits.reason),
+        ];
+        if let Some((field, _)) = fields.iter().find(|(_, limit)| limit.min > limit.max) {
+            return Err(ContractError::InvalidContentLimits { field: field.to_string() });
+        }
+        config.content_limits = content_limits;
+        Ok(config)
+    })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_content_limits")
+        .add_attribute("author", info.sender),
+    )
+}
+
+pub fn update_cw20_fee(deps: DepsMut, info: MessageInfo, token: Addr, thread_fee: Uint128, comment_fee: Uint128) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let token = deps.api.addr_validate(token.as_str())?;
+    CW20_FEES.save(deps.storage, &token, &Cw20Fee { token: token.clone(), thread_fee, comment_fee })?;
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "update_cw20_fee")
+        .add_attribute("token", token)
+        .add_attribute("thread_fee", thread_fee)
+        .add_attribute("comment_fee", comment_fee),
+    )
+}
+
+pub fn remove_cw20_fee(deps: DepsMut, info: MessageInfo, token: Addr) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !CW20_FEES.has(deps.storage, &token) {
+        return Err(ContractError::Cw20TokenNotAccepted {});
+    }
+
+    CW20_FEES.remove(deps.storage, &token);
+
+    Ok(
+        Response::new()
+        .add_attribute("method", "remove_cw20_fee")
+        .add_attribute("token", token),
+    )
+}
+
+fn send_cw20(deps: DepsMut, env: Env, info: MessageInfo, token: Addr, address: Addr, amount: Uint128) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_admin(&config, &info.sender) {
+        return Err(ContractError::Unauthorized { });
+    }
+
+    let balance: BalanceResponse = deps.querier.query_wasm_smart(token.clone(), &Cw20QueryMsg::Balance { address: env.contract.address.to_string() })?;
+    let reserved = TOTAL_CLAIMABLE_CW20.may_load(deps.storage, &token)?.unwrap_or_default();
+    if amount.checked_add(reserved).map_err(|_| ContractError::NotEnoughBalance {})? > balance.balance {
+        return Err(ContractError::NotEnoughBalance { });
+    }
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: address.to_string(), amount })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new().add_message(msg))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetThreadById { id } => to_binary(&query_thread_by_id(deps, id)?),
+        QueryMsg::GetThreadsByCategory {category, start_after, limit, order} => to_binary(&query_threads_by_category(deps, category, start_after, limit, order)?),
+        QueryMsg::GetThreadsByAuthor { author, start_after, limit, order } =>  to_binary(&query_threads_by_author(deps, author, start_after, limit, order)?),
+        QueryMsg::GetCommentById {id} => to_binary(&query_comment_by_id(deps, id)?),
+        QueryMsg::GetCommentsByThread { thread_id, start_after, limit, order } => to_binary(&query_comments_by_thread(deps, thread_id, start_after, limit, order)?),
+        QueryMsg::GetConfig {  } => to_binary(&query_config(deps)?),
+        QueryMsg::GetModerators { start_after, limit } => to_binary(&query_moderators(deps, start_after, limit)?),
+        QueryMsg::GetModeratorsByCategory { category, start_after, limit } => to_binary(&query_moderators_by_category(deps, category, start_after, limit)?),
+        QueryMsg::GetModeratorCategories { address } => to_binary(&query_moderator_categories(deps, address)?),
+        QueryMsg::GetCategories { include_archived } => to_binary(&query_categories(deps, include_archived.unwrap_or(false))?),
+        QueryMsg::GetThreadRevisions { id, start_after, limit } => to_binary(&query_revisions(deps, Target::Thread, id, start_after, limit)?),
+        QueryMsg::GetCommentRevisions { comment_id, start_after, limit } => to_binary(&query_revisions(deps, Target::Comment, comment_id, start_after, limit)?),
+        QueryMsg::GetReplies { comment_id, start_after, limit } => to_binary(&query_replies(deps, comment_id, start_after, limit)?),
+        QueryMsg::GetCommentTree { thread_id, parent_comment_id, depth, limit } => to_binary(&query_comment_tree(deps, thread_id, parent_comment_id, depth, limit)?),
+        QueryMsg::GetVote { voter, target, id } => to_binary(&query_vote(deps, voter, target, id)?),
+        QueryMsg::GetReactionCounts { target, id } => to_binary(&query_reaction_counts(deps, target, id)?),
+        QueryMsg::GetReactionsByAddress { address, target, id } => to_binary(&query_reactions_by_address(deps, address, target, id)?),
+        QueryMsg::GetAllowedReactions {} => to_binary(&query_allowed_reactions(deps)?),
+        QueryMsg::GetTips { target, id } => to_binary(&query_tips(deps, target, id)?),
+        QueryMsg::GetAuthorTips { author } => to_binary(&query_author_tips(deps, author)?),
+        QueryMsg::GetCw20Fees {} => to_binary(&query_cw20_fees(deps)?),
+        QueryMsg::GetClaimable { address } => to_binary(&query_claimable(deps, address)?),
+        QueryMsg::GetPendingAdmin {} => to_binary(&PENDING_ADMIN.may_load(deps.storage)?),
+        QueryMsg::GetThreads { start_after, limit, order } => to_binary(&query_threads(deps, start_after, limit, order)?),
+        QueryMsg::GetComments { start_after, limit } => to_binary(&query_comments(deps, start_after, limit)?),
+        QueryMsg::GetCounts {} => to_binary(&query_counts(deps)?),
+        QueryMsg::GetCommentsByAuthor { author, start_after, limit } => to_binary(&query_comments_by_author(deps, author, start_after, limit)?),
+        QueryMsg::GetPinnedThreads { category } => to_binary(&query_pinned_threads(deps, category)?),
+        QueryMsg::GetBans { start_after, limit } => to_binary(&query_bans(deps, start_after, limit)?)
+    }
+}
+
+fn query_thread_by_id(deps: Deps, id: u64) -> StdResult<Thread> {
+    let thread = threads().load(deps.storage, &id.to_be_bytes())?;
+    Ok(thread)
+}
+
+// Limits for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+type PageBound<'a, K> = Option<Bound<'a, K>>;
+
+// Cursor bounds over id keyed listings, the cursor is excluded in either direction
+fn page_bounds<'a, K: PrimaryKey<'a>>(cursor: Option<K>, order: Order) -> (PageBound<'a, K>, PageBound<'a, K>) {
+    let cursor = cursor.map(Bound::exclusive);
+    match order {
+        Order::Ascending => (cursor, None),
+        Order::Descending => (None, cursor),
+    }
+}
+
+// A short page means there is nothing left to fetch
+fn next_start_after(len: usize, limit: usize, last_id: Option<u64>) -> Option<u64> {
+    if len < limit {
+        return None;
+    }
+    last_id
+}
+
+// Latest threads across every category
+fn query_threads(deps: Deps, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<ThreadsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or_default().into();
+    let cursor = start_after.map(|id| id.to_be_bytes());
+    let (min, max) = page_bounds(cursor.as_ref().map(|id| id.as_slice()), order);
+
+    let list: StdResult<Vec<_>> = threads()
+    .range(deps.storage, min, max, order)
+    .filter(|item| !matches!(item, Ok((_, t)) if t.deleted))
+    .take(limit)
+    .map(|item| item.map(|(_, t)| t))
+    .collect();
+    let entries = list?;
+
+    Ok(ThreadsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|t| t.id)),
+        entries,
+    })
+}
+
+// Latest comments across every thread, newest first
+fn query_comments(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<CommentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let cursor = start_after.map(|id| id.to_be_bytes());
+    let (min, max) = page_bounds(cursor.as_ref().map(|id| id.as_slice()), Order::Descending);
+
+    // Comments of deleted threads are hidden together with their thread
+    let mut thread_deleted: HashMap<u64, bool> = HashMap::new();
+    let mut entries: Vec<Comment> = vec![];
+    for item in comments().range(deps.storage, min, max, Order::Descending) {
+        let (_, comment) = item?;
+        if comment.deleted {
+            continue;
+        }
+        let deleted = match thread_deleted.get(&comment.thread_id) {
+            Some(deleted) => *deleted,
+            None => {
+                let deleted = threads().load(deps.storage, &comment.thread_id.to_be_bytes())?.deleted;
+                thread_deleted.insert(comment.thread_id, deleted);
+                deleted
+            },
+        };
+        if deleted {
+            continue;
+        }
+        entries.push(comment);
+        if entries.len() == limit {
+            break;
+        }
+    }
+
+    Ok(CommentsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|c| c.comment_id)),
+        entries,
+    })
+}
+
+fn query_counts(deps: Deps) -> StdResult<CountsResponse> {
+    Ok(CountsResponse {
+        thread_counter: THREAD_COUNTER.may_load(deps.storage)?.unwrap_or_default(),
+        comment_counter: COMMENT_COUNTER.may_load(deps.storage)?.unwrap_or_default(),
+        live_threads: LIVE_THREADS.may_load(deps.storage)?.unwrap_or_default(),
+        live_comments: LIVE_COMMENTS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+// Most recently pinned first, the bounded pin count makes a single page enough
+fn query_pinned_threads(deps: Deps, category: Option<String>) -> StdResult<ThreadsResponse> {
+    let key = category.map(|category| category_key(&category)).unwrap_or_else(|| GLOBAL_PIN_SCOPE.to_string());
+    let mut pins = PINS
+    .prefix(&key)
+    .range(deps.storage, None, None, Order::Ascending)
+    .collect::<StdResult<Vec<_>>>()?;
+    pins.sort_by_key(|(_, pinned_at)| std::cmp::Reverse(*pinned_at));
+
+    let mut entries = vec![];
+    for (id, _) in pins {
+        let thread = threads().load(deps.storage, &id.to_be_bytes())?;
+        if !thread.deleted {
+            entries.push(thread);
+        }
+    }
+
+    Ok(ThreadsResponse {
+        entries,
+        next_start_after: None,
+    })
+}
+
+fn query_threads_by_category(deps: Deps, category: String, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<ThreadsResponse> {
+    let category = resolve_category(deps.storage, category)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or_default().into();
+    let (min, max) = page_bounds(start_after.map(|id| id.to_be_bytes().to_vec()), order);
+   
+    let list: StdResult<Vec<_>>  = threads()
+    .idx.category
+    .prefix(category)
+    .range(deps.storage, min, max, order)
+    .filter(|item| !matches!(item, Ok((_, t)) if t.deleted))
+    .take(limit)
+    .map(|item| item.map(|(_, t)| t))
+    .collect();
+    let entries = list?;
+
+    let result = ThreadsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|t| t.id)),
+        entries,
+    };
+    Ok(result)    
+}
+
+fn query_threads_by_author(deps: Deps, author: Addr, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<ThreadsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or_default().into();
+    let (min, max) = page_bounds(start_after.map(|id| id.to_be_bytes().to_vec()), order);
+
+    let list: StdResult<Vec<_>>  = threads()
+    .idx.author
+    .prefix(author)
+    .range(deps.storage, min, max, order)
+    .filter(|item| !matches!(item, Ok((_, t)) if t.deleted))
+    .take(limit)
+    .map(|item| item.map(|(_, t)| t))
+    .collect();
+    let entries = list?;
+
+    let result = ThreadsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|t| t.id)),
+        entries,
+    };
+    Ok(result)    
+}
+
+fn query_comment_by_id(deps: Deps, comment_id: u64) -> StdResult<Comment> {
+    let comment = comments().load(deps.storage, &comment_id.to_be_bytes())?;
+    Ok(comment)
+}
+
+fn query_comments_by_thread(deps: Deps, thread_id: u64, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>) -> StdResult<CommentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or_default().into();
+    let (min, max) = page_bounds(start_after.map(|id| id.to_be_bytes().to_vec()), order);
+
+    let list: StdResult<Vec<_>>  = comments()
+    .idx.thread
+    .prefix(thread_id.to_be_bytes().to_vec())
+    .range(deps.storage, min, max, order)
+    .take(limit)
+    .map(|item| item.map(|(_, comment)| comment))
+    .collect();
+    let entries = list?;
+
+    let result = CommentsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|c| c.comment_id)),
+        entries,
+    };
+    Ok(result)    
+}
+
+// Comment history of an address, newest first
+fn query_comments_by_author(deps: Deps, author: Addr, start_after: Option<u64>, limit: Option<u32>) -> StdResult<CommentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (min, max) = page_bounds(start_after.map(|id| id.to_be_bytes().to_vec()), Order::Descending);
+
+    let list: StdResult<Vec<_>> = comments()
+    .idx.author
+    .prefix(author)
+    .range(deps.storage, min, max, Order::Descending)
+    .filter(|item| !matches!(item, Ok((_, c)) if c.deleted))
+    .take(limit)
+    .map(|item| item.map(|(_, comment)| comment))
+    .collect();
+    let entries = list?;
+
+    Ok(CommentsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|c| c.comment_id)),
+        entries,
+    })
+}
+
+fn query_revisions(deps: Deps, target: Target, id: u64, start_after: Option<u32>, limit: Option<u32>) -> StdResult<RevisionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let list: StdResult<Vec<_>> = REVISIONS
+    .prefix((target.key(), id))
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, revision)| revision))
+    .collect();
+
+    Ok(RevisionsResponse {
+        entries: list?,
+    })
+}
+
+// Replies are returned oldest first, like a conversation is read
+fn query_replies(deps: Deps, comment_id: u64, start_after: Option<u64>, limit: Option<u32>) -> StdResult<CommentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let comment = comments().load(deps.storage, &comment_id.to_be_bytes())?;
+    let start = start_after.map(|id| Bound::exclusive(id.to_be_bytes().to_vec()));
+
+    let list: StdResult<Vec<_>> = comments()
+    .idx.parent
+    .prefix(comment_parent_key(comment.thread_id, Some(comment_id)))
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, reply)| reply))
+    .collect();
+    let entries = list?;
+
+    Ok(CommentsResponse {
+        next_start_after: next_start_after(entries.len(), limit, entries.last().map(|c| c.comment_id)),
+        entries,
+    })
+}
+
+// Limits for comment trees
+const MAX_TREE_DEPTH: u32 = 5;
+const DEFAULT_TREE_DEPTH: u32 = 3;
+const MAX_TREE_NODES: usize = 100;
+
+fn query_comment_tree(deps: Deps, thread_id: u64, parent_comment_id: Option<u64>, depth: Option<u32>, limit: Option<u32>) -> StdResult<CommentTreeResponse> {
+    let depth = depth.unwrap_or(DEFAULT_TREE_DEPTH).min(MAX_TREE_DEPTH);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut budget = MAX_TREE_NODES;
+
+    Ok(CommentTreeResponse {
+        entries: comment_subtree(deps, thread_id, parent_comment_id, depth, limit, &mut budget)?,
+    })
+}
+
+// Walks the parent index depth first, every level takes up to `limit` replies
+// and the whole tree never holds more than MAX_TREE_NODES comments
+fn comment_subtree(deps: Deps, thread_id: u64, parent_comment_id: Option<u64>, depth: u32, limit: usize, budget: &mut usize) -> StdResult<Vec<CommentNode>> {
+    if depth == 0 || *budget == 0 {
+        return Ok(vec![]);
+    }
+
+    let children = comments()
+    .idx.parent
+    .prefix(comment_parent_key(thread_id, parent_comment_id))
+    .range(deps.storage, None, None, Order::Ascending)
+    .take(limit.min(*budget))
+    .map(|item| item.map(|(_, comment)| comment))
+    .collect::<StdResult<Vec<Comment>>>()?;
+    *budget -= children.len();
+
+    let mut nodes = Vec::with_capacity(children.len());
+    for comment in children {
+        let replies = comment_subtree(deps, thread_id, Some(comment.comment_id), depth - 1, limit, budget)?;
+        nodes.push(CommentNode { comment, replies });
+    }
+    Ok(nodes)
+}
+
+fn query_vote(deps: Deps, voter: Addr, target: Target, id: u64) -> StdResult<VoteResponse> {
+    Ok(VoteResponse {
+        direction: VOTES.may_load(deps.storage, (target.key(), id, &voter))?,
+    })
+}
+
+// Bounded by the allowlist, so no pagination is needed
+fn query_reaction_counts(deps: Deps, target: Target, id: u64) -> StdResult<ReactionCountsResponse> {
+    let reactions: StdResult<Vec<_>> = REACTION_COUNTS
+    .prefix((target.key(), id))
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(reaction, count)| ReactionCount { reaction, count }))
+    .collect();
+
+    Ok(ReactionCountsResponse {
+        reactions: reactions?,
+    })
+}
+
+fn query_reactions_by_address(deps: Deps, address: Addr, target: Target, id: u64) -> StdResult<ReactionsResponse> {
+    Ok(ReactionsResponse {
+        reactions: REACTIONS.may_load(deps.storage, (target.key(), id, &address))?.unwrap_or_default(),
+    })
+}
+
+fn query_allowed_reactions(deps: Deps) -> StdResult<ReactionsResponse> {
+    Ok(ReactionsResponse {
+        reactions: ALLOWED_REACTIONS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_tips(deps: Deps, target: Target, id: u64) -> StdResult<TipsResponse> {
+    let tips: StdResult<Vec<_>> = TIP_TOTALS
+    .prefix((target.key(), id))
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+    .collect();
+
+    Ok(TipsResponse {
+        tips: tips?,
+    })
+}
+
+fn query_author_tips(deps: Deps, author: Addr) -> StdResult<TipsResponse> {
+    let tips: StdResult<Vec<_>> = AUTHOR_TIPS
+    .prefix(&author)
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+    .collect();
+
+    Ok(TipsResponse {
+        tips: tips?,
+    })
+}
+
+fn query_claimable(deps: Deps, address: Addr) -> StdResult<ClaimableResponse> {
+    let native: StdResult<Vec<_>> = CLAIMABLE
+    .prefix(&address)
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+    .collect();
+    let cw20: StdResult<Vec<_>> = CLAIMABLE_CW20
+    .prefix(&address)
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(token, amount)| Cw20Coin { address: token.to_string(), amount }))
+    .collect();
+
+    Ok(ClaimableResponse {
+        native: native?,
+        cw20: cw20?,
+    })
+}
+
+// Bounded by the admin whitelist, so no pagination is needed
+fn query_cw20_fees(deps: Deps) -> StdResult<Cw20FeesResponse> {
+    let fees: StdResult<Vec<_>> = CW20_FEES
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(_, fee)| fee))
+    .collect();
+
+    Ok(Cw20FeesResponse {
+        fees: fees?,
+    })
+}
+
+fn query_config(deps: Deps) -> StdResult<Config> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(config)
+}
+
+fn query_moderators(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<ModeratorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let list: StdResult<Vec<_>> = MODERATORS
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, moderator)| moderator))
+    .collect();
+
+    Ok(ModeratorsResponse {
+        moderators: list?,
+    })
+}
+
+// Expired bans stay listed until a moderator removes them
+fn query_bans(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<BansResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let list: StdResult<Vec<_>> = BANS
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, ban)| ban))
+    .collect();
+
+    Ok(BansResponse {
+        bans: list?,
+    })
+}
+
+// Lists moderators scoped to the category, unscoped moderators are not included
+fn query_moderators_by_category(deps: Deps, category: String, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<ModeratorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let category = resolve_category(deps.storage, category)?;
+    let start = start_after.map(|address| Bound::exclusive((address, category.clone())));
+
+    let list: StdResult<Vec<_>> = moderator_scopes()
+    .idx.category
+    .prefix(category.clone())
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, scope)| Moderator { address: scope.moderator, scoped: true }))
+    .collect();
+
+    Ok(ModeratorsResponse {
+        moderators: list?,
+    })
+}
+
+fn query_moderator_categories(deps: Deps, address: Addr) -> StdResult<ModeratorCategoriesResponse> {
+    let moderator = MODERATORS.load(deps.storage, &address)?;
+    let categories: StdResult<Vec<String>> = moderator_scopes()
+    .prefix(address)
+    .keys(deps.storage, None, None, Order::Ascending)
+    .collect();
+
+    Ok(ModeratorCategoriesResponse {
+        scoped: moderator.scoped,
+        categories: categories?,
+    })
+}
+
+// The registry is small and admin managed, so it is returned whole in display order
+fn query_categories(deps: Deps, include_archived: bool) -> StdResult<CategoriesResponse> {
+    let mut categories = CATEGORIES
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(_, category)| category))
+    .filter(|item| !matches!(item, Ok(category) if category.archived && !include_archived))
+    .collect::<StdResult<Vec<Category>>>()?;
+    categories.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(CategoriesResponse {
+        categories,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{LegacyConfig, PendingAdmin, UPGRADE_PROGRESS, UpgradeProgress};
+    use cw_storage_plus::Index;
+    use crate::msg::GetThreadByIdResponse;
+    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info, MockQuerier, MockApi};
+    use cosmwasm_std::{coins, from_binary, OwnedDeps, MemoryStorage, Timestamp, SystemResult, ContractResult};
+
+    fn instantiate_contract() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let msg = InstantiateMsg { thread_fee: Option::Some(coins(10000, "uluna")), comment_fee: Option::Some(coins(10000, "uluna")), comment_cascade: None, tip_cut: None};
+        let info = mock_info("creator", &coins(1000000, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        create_new_category(deps.as_mut(), "General");
+        deps
+    }
+
+    fn create_new_category(deps: DepsMut, name: &str) {
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CreateCategory { name: String::from(name), description: None, sort_order: None, owner: None };
+        let _res = execute(deps, mock_env(), info, msg).unwrap();
+    }
+
+    fn create_new_thread(deps: DepsMut) {
+        let info = mock_info("creator", &coins(10000000, "uluna"));
+        let title = String::from("First Thread");
+        let content = String::from("First Message");
+        let category = String::from("General");
+        let msg = ExecuteMsg::CreateThread { title: title.clone(), content: content.clone(), category: category.clone()};
+        let _res = execute(deps, mock_env(), info, msg);
     }
 
     fn create_new_comment(deps: DepsMut, info: MessageInfo) {
-        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("New Comment")};
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("New Comment"), parent_comment_id: None};
         let _res = execute(deps, mock_env(), info.clone(), msg);
     }
 
     #[test]
-    fn create_thread() {
+    fn create_thread() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+
+        //Expect Fee Error
+        let info = mock_info("creator", &coins(0, "uluna"));
+        let title = String::from("First Thread");
+        let content = String::from("First Message");
+        let category = String::from("General");
+        let msg = ExecuteMsg::CreateThread { title: title.clone(), content: content.clone(), category: category.clone()};
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        
+        match res {
+            Err(ContractError::LessFeeAmount {  } ) => {}
+            _ => panic!("Must return less fee amount error"),
+        }
+         // We should query thread response using id
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.id);
+        assert_eq!(String::from("First Thread"), value.title);
+        assert_eq!(String::from("First Message"), value.content);
+        assert_eq!(String::from("General"), value.category);
+
+    }
+
+    #[test]
+    fn update_thread() {
+        let mut deps = instantiate_contract();
+        
+        create_new_thread(deps.as_mut());
+
+        let updated_title = String::from("Updated Title");
+        let updated_content = String::from("Updated Content");
+
+        // Should return error if not executed by thread author
+        let info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThread{ id: 1, title: updated_title.clone(), content: updated_content.clone()};
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Should update content for author
+        let info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThread { id: 1, title: updated_title.clone(), content: updated_content.clone()};
+        let _res = execute(deps.as_mut(), mock_env(), info, msg);
+
+         // Verify content is updated
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
+        assert_eq!(updated_title, value.title);
+        assert_eq!(updated_content, value.content);
+
+    }
+    #[test]
+    fn update_thread_content() {
+        let mut deps = instantiate_contract();
+        
+        create_new_thread(deps.as_mut());
+
+        let updated_content = String::from("Updated Content!");
+
+        // Should return error if not executed by thread author
+        let info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThreadContent { id: 1, content: updated_content.clone()};
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Should update content for author
+        let info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThreadContent { id: 1, content: updated_content.clone()};
+        let _res = execute(deps.as_mut(), mock_env(), info, msg);
+
+         // Verify content is updated
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
+        assert_eq!(updated_content, value.content);
+
+    }
+
+    #[test]
+    fn update_thread_title() {
+        let mut deps = instantiate_contract();
+        
+        create_new_thread(deps.as_mut());
+
+        let updated_title = String::from("Updated Title");
+
+        // Should return error if not executed by thread author
+        let info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: updated_title.clone()};
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Should update content for author
+        let info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: updated_title.clone()};
+        let _res = execute(deps.as_mut(), mock_env(), info, msg);
+
+         // Verify content is updated
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
+        assert_eq!(updated_title, value.title);
+
+    }
+
+    #[test]
+    fn add_comment() {
+        let mut deps = instantiate_contract();
+        let info = mock_info("creator", &coins(10000, "uluna"));
+        let comment = String::from("New Reply");
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: comment.clone(), parent_comment_id: None};
+        // Add Reply Without Creating Thread
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+        match res {
+            Err(ContractError::ThreadNotExists{}) => {}
+            _ => panic!("Must return thread not exists error"),
+        }
+
+        create_new_thread(deps.as_mut());
+
+        // Check Fee Error
+        let fee_info = mock_info("creator", &coins(10, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), fee_info.clone(), msg.clone());
+        match res {
+            Err(ContractError::LessFeeAmount {  } ) => {}
+            _ => panic!("Must return less fee amount error"),
+        }
+        // Add Reply After Creating Thread
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+
+        // Verify Comment is added
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
+        let value: Comment = from_binary(&res).unwrap();
+        assert_eq!(1, value.thread_id);
+
+    }
+
+    #[test]
+    fn update_comment() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+
+        let auth_info = mock_info("creator", &coins(10000, "uluna"));
+        // Create A Comment
+        create_new_comment(deps.as_mut(), auth_info.clone());
+
+        let update_comment_msg = ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Updated Comment")};
+       
+        // Update Without Authorized User
+        let un_auth_info = mock_info("anon", &coins(10000, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), un_auth_info.clone(), update_comment_msg.clone());
+
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Update With Authorized User
+        let _res = execute(deps.as_mut(), mock_env(), auth_info.clone(), update_comment_msg);
+
+        // Verify Updated Comment
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
+        let value: Comment = from_binary(&res).unwrap();
+        assert_eq!(String::from("Updated Comment"), value.comment);
+
+    }
+
+    #[test]
+    fn delete_comment() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+
+        let commenter_info = mock_info("commenter", &coins(10000, "uluna"));
+        create_new_comment(deps.as_mut(), commenter_info.clone());
+        create_new_comment(deps.as_mut(), commenter_info.clone());
+        create_new_comment(deps.as_mut(), commenter_info.clone());
+
+        // Should return error if not executed by comment author, thread author or admin
+        let info = mock_info("anon", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteComment { comment_id: 1, hard: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Comment author, and thread author who is also the admin here
+        let _res = execute(deps.as_mut(), mock_env(), commenter_info.clone(), ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Edited") }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), commenter_info.clone(), ExecuteMsg::DeleteComment { comment_id: 1, hard: None }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::DeleteComment { comment_id: 3, hard: None }).unwrap();
+
+        // Deleted twice or updated after deletion
+        let res = execute(deps.as_mut(), mock_env(), commenter_info.clone(), ExecuteMsg::DeleteComment { comment_id: 1, hard: None });
+        match res {
+            Err(ContractError::CommentDeleted {}) => {}
+            _ => panic!("Must return comment deleted error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), commenter_info, ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Again") });
+        match res {
+            Err(ContractError::CommentDeleted {}) => {}
+            _ => panic!("Must return comment deleted error"),
+        }
+
+        // Thread listing returns placeholders in place of deleted comments
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByThread {thread_id: 1_u64, start_after: None, limit: None, order: None}).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.entries.len());
+        assert!(value.entries[0].deleted);
+        assert_eq!(String::new(), value.entries[0].comment);
+        assert!(!value.entries[1].deleted);
+        assert!(value.entries[2].deleted);
+
+        // Soft delete keeps the edit history, a later hard delete drops it
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentRevisions { comment_id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::DeleteComment { comment_id: 1, hard: Some(true) }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentRevisions { comment_id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert!(value.entries.is_empty());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCounts {}).unwrap();
+        let value: CountsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.live_comments);
+    }
+
+    #[test]
+    fn moderators() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        let commenter_info = mock_info("commenter", &coins(10000, "uluna"));
+        create_new_comment(deps.as_mut(), commenter_info);
+
+        create_new_category(deps.as_mut(), "Anchor");
+        let moderator_info = mock_info("moderator", &[]);
+        let add_msg = ExecuteMsg::AddModerator { address: moderator_info.sender.clone(), categories: None };
+
+        // Only admin can add moderators
+        let res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), add_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let admin_info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), add_msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::AddModerator { address: Addr::unchecked("another"), categories: None }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetModerators { start_after: None, limit: None }).unwrap();
+        let value: ModeratorsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.moderators.len());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetModerators { start_after: Some(Addr::unchecked("another")), limit: None }).unwrap();
+        let value: ModeratorsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![Moderator { address: moderator_info.sender.clone(), scoped: false }], value.moderators);
+
+        // Moderator can move, hide comments and threads
+        let _res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), ExecuteMsg::MoveThread { id: 1, category: String::from("Anchor") }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("Anchor"), start_after: None, limit: None, order: None}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        let _res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), ExecuteMsg::DeleteComment { comment_id: 1, hard: None }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), ExecuteMsg::DeleteThread { id: 1, hard: None }).unwrap();
+
+        // Moderator cannot touch fees or funds
+        let res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), ExecuteMsg::UpdateFees { thread_fee: None, comment_fee: None, tip_cut: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), moderator_info.clone(), ExecuteMsg::Send { address: moderator_info.sender.clone(), amount: Uint128::from(1_u128), denom: String::from("uluna") });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Removed moderator loses access
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::RemoveModerator { address: moderator_info.sender.clone() }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::RemoveModerator { address: moderator_info.sender.clone() });
+        match res {
+            Err(ContractError::ModeratorNotExists {}) => {}
+            _ => panic!("Must return moderator not exists error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), moderator_info, ExecuteMsg::MoveThread { id: 1, category: String::from("General") });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+
+    #[test]
+    fn moderator_scopes() {
+        let mut deps = instantiate_contract();
+        create_new_category(deps.as_mut(), "Anchor");
+        create_new_category(deps.as_mut(), "Governance");
+        let info = mock_info("author", &coins(10000, "uluna"));
+        let msg = ExecuteMsg::CreateThread { title: String::from("Anchor Thread"), content: String::from("Content"), category: String::from("Anchor")};
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreateThread { title: String::from("Governance Thread"), content: String::from("Content"), category: String::from("Governance")};
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let admin_info = mock_info("creator", &[]);
+        let anchor_mod = mock_info("anchor_mod", &[]);
+        let msg = ExecuteMsg::AddModerator { address: anchor_mod.sender.clone(), categories: Some(vec![String::from("Anchor"), String::from("General")]) };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AddModerator { address: Addr::unchecked("gov_mod"), categories: Some(vec![String::from("Governance")]) };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+
+        // Scoped moderator cannot act outside their categories
+        let res = execute(deps.as_mut(), mock_env(), anchor_mod.clone(), ExecuteMsg::DeleteThread { id: 2, hard: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), anchor_mod.clone(), ExecuteMsg::MoveThread { id: 1, category: String::from("Governance") });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), anchor_mod.clone(), ExecuteMsg::MoveThread { id: 1, category: String::from("General") }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), anchor_mod.clone(), ExecuteMsg::DeleteThread { id: 1, hard: None }).unwrap();
+
+        // Query moderators by category and categories by moderator
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetModeratorsByCategory { category: String::from("Governance"), start_after: None, limit: None }).unwrap();
+        let value: ModeratorsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![Moderator { address: Addr::unchecked("gov_mod"), scoped: true }], value.moderators);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetModeratorCategories { address: anchor_mod.sender.clone() }).unwrap();
+        let value: ModeratorCategoriesResponse = from_binary(&res).unwrap();
+        assert!(value.scoped);
+        assert_eq!(vec![String::from("Anchor"), String::from("General")], value.categories);
+
+        // Removing the moderator clears their scopes
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::RemoveModerator { address: anchor_mod.sender.clone() }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetModeratorsByCategory { category: String::from("Anchor"), start_after: None, limit: None }).unwrap();
+        let value: ModeratorsResponse = from_binary(&res).unwrap();
+        assert!(value.moderators.is_empty());
+    }
+
+    #[test]
+    fn categories() {
+        let mut deps = instantiate_contract();
+        let admin_info = mock_info("creator", &[]);
+        let author_info = mock_info("author", &coins(10000, "uluna"));
+
+        // Only admin can manage categories
+        let msg = ExecuteMsg::CreateCategory { name: String::from("Anchor"), description: Some(String::from("Anchor Protocol")), sort_order: Some(2), owner: None };
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+
+        // Names are unique regardless of case and padding
+        let msg = ExecuteMsg::CreateCategory { name: String::from("general "), description: None, sort_order: None, owner: None };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg);
+        match res {
+            Err(ContractError::CategoryExists {}) => {}
+            _ => panic!("Must return category exists error"),
+        }
+
+        // Threads land in the registered bucket
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from(" GENERAL ")};
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("general"), start_after: None, limit: None, order: None}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        assert_eq!(String::from("General"), value.entries[0].category);
+
+        // Unknown and archived categories are rejected
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("Unknown")};
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg);
+        match res {
+            Err(ContractError::CategoryNotExists {}) => {}
+            _ => panic!("Must return category not exists error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::ArchiveCategory { name: String::from("Anchor") }).unwrap();
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("Anchor")};
+        let res = execute(deps.as_mut(), mock_env(), author_info, msg);
+        match res {
+            Err(ContractError::CategoryArchived {}) => {}
+            _ => panic!("Must return category archived error"),
+        }
+
+        // Archived categories are hidden unless requested, list follows sort order
+        let msg = ExecuteMsg::UpdateCategory { name: String::from("General"), description: Some(String::from("Anything goes")), sort_order: Some(5), archived: None, owner: None, clear_owner: None };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCategories { include_archived: None }).unwrap();
+        let value: CategoriesResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.categories.len());
+        assert_eq!(String::from("Anything goes"), value.categories[0].description);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCategories { include_archived: Some(true) }).unwrap();
+        let value: CategoriesResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.categories.len());
+        assert_eq!(String::from("Anchor"), value.categories[0].name);
+        assert!(value.categories[0].archived);
+    }
+
+    #[test]
+    fn block_metadata() {
+        let mut deps = instantiate_contract();
+        let mut env = mock_env();
+        let info = mock_info("creator", &coins(10000, "uluna"));
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General")};
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Comment"), parent_comment_id: None};
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(env.block.time, value.created_at);
+        assert_eq!(env.block.height, value.created_height);
+        assert_eq!(env.block.time, value.updated_at);
+
+        // Edits only move updated_at
+        let created_at = env.block.time;
+        env.block.height += 10;
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: String::from("New Title")};
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("New Comment")};
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(created_at, value.created_at);
+        assert_eq!(env.block.time, value.updated_at);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetCommentById {id: 1}).unwrap();
+        let value: Comment = from_binary(&res).unwrap();
+        assert_eq!(created_at, value.created_at);
+        assert_eq!(env.block.time, value.updated_at);
+    }
+
+    #[test]
+    fn migrate_backfills_timestamps() {
         let mut deps = instantiate_contract();
         create_new_thread(deps.as_mut());
+        create_new_comment(deps.as_mut(), mock_info("creator", &coins(10000, "uluna")));
 
-        //Expect Fee Error
-        let info = mock_info("creator", &coins(0, "uluna"));
-        let title = String::from("First Thread");
-        let content = String::from("First Message");
-        let category = String::from("General");
-        let msg = ExecuteMsg::CreateThread { title: title.clone(), content: content.clone(), category: category.clone()};
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-        
+        // Simulate records written before the metadata fields existed
+        let legacy = threads().load(&deps.storage, &1_u64.to_be_bytes()).unwrap();
+        let legacy = Thread { created_at: Timestamp::default(), created_height: 0, updated_at: Timestamp::default(), ..legacy };
+        threads().save(&mut deps.storage, &1_u64.to_be_bytes(), &legacy).unwrap();
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg { legacy_fee_denom: None }).unwrap();
+        assert_eq!(("threads_stamped", "1"), (res.attributes[3].key.as_str(), res.attributes[3].value.as_str()));
+        assert_eq!(("comments_stamped", "0"), (res.attributes[4].key.as_str(), res.attributes[4].value.as_str()));
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(env.block.height, value.created_height);
+        assert_eq!(env.block.time, value.created_at);
+    }
+
+    #[test]
+    fn migrate_step() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        create_new_thread(deps.as_mut());
+        create_new_comment(deps.as_mut(), mock_info("creator", &coins(10000, "uluna")));
+        create_new_comment(deps.as_mut(), mock_info("creator", &coins(10000, "uluna")));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::MigrateStep { limit: None });
         match res {
-            Err(ContractError::LessFeeAmount {  } ) => {}
+            Err(ContractError::NoUpgradePending {}) => {}
+            _ => panic!("Must return no upgrade pending error"),
+        }
+
+        // Simulate an upgrade that did not fit in the migrate transaction
+        UPGRADE_PROGRESS.save(&mut deps.storage, &UpgradeProgress { threads_done: false, last_id: None }).unwrap();
+        LIVE_THREADS.save(&mut deps.storage, &0).unwrap();
+        LIVE_COMMENTS.save(&mut deps.storage, &0).unwrap();
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General") };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(10000, "uluna")), msg.clone());
+        match res {
+            Err(ContractError::UpgradePending {}) => {}
+            _ => panic!("Must return upgrade pending error"),
+        }
+
+        // One record per step, threads first and then comments
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::MigrateStep { limit: Some(1) }).unwrap();
+            if res.attributes[5].value == "true" {
+                break;
+            }
+        }
+        assert_eq!(5, steps);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCounts {}).unwrap();
+        let value: CountsResponse = from_binary(&res).unwrap();
+        assert_eq!((2, 2), (value.live_threads, value.live_comments));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(10000, "uluna")), msg).unwrap();
+    }
+
+    #[test]
+    fn revisions() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        let info = mock_info("creator", &coins(10000, "uluna"));
+        create_new_comment(deps.as_mut(), info.clone());
+
+        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: String::from("Second Title")};
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::UpdateThread { id: 1, title: String::from("Third Title"), content: String::from("Third Content")};
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Edited Comment")};
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Entity carries the revision counter, the log keeps previous text
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(2, value.revision);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadRevisions { id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.entries.len());
+        assert_eq!(Some(String::from("First Thread")), value.entries[0].title);
+        assert_eq!(String::from("First Message"), value.entries[0].content);
+        assert_eq!(Some(String::from("Second Title")), value.entries[1].title);
+        assert_eq!(info.sender, value.entries[1].editor);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadRevisions { id: 1, start_after: Some(0), limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].revision);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentRevisions { comment_id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        assert_eq!(None, value.entries[0].title);
+        assert_eq!(String::from("New Comment"), value.entries[0].content);
+
+        // Hard deleting the comment and the thread purges their history
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::DeleteComment { comment_id: 1, hard: Some(true) }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteThread { id: 1, hard: Some(true) }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentRevisions { comment_id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert!(value.entries.is_empty());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadRevisions { id: 1, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert!(value.entries.is_empty());
+    }
+
+    #[test]
+    fn nested_replies() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        create_new_thread(deps.as_mut());
+        let info = mock_info("creator", &coins(10000, "uluna"));
+        let reply = |parent: u64| ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Reply"), parent_comment_id: Some(parent) };
+
+        // 1 -> 2 -> 3, 1 -> 4, 5
+        create_new_comment(deps.as_mut(), info.clone());
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), reply(1)).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), reply(2)).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), reply(1)).unwrap();
+        create_new_comment(deps.as_mut(), info.clone());
+
+        // Parent must exist in the same thread
+        let msg = ExecuteMsg::AddComment { thread_id: 2, comment: String::from("Reply"), parent_comment_id: Some(1) };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::ParentCommentMismatch {}) => {}
+            _ => panic!("Must return parent comment mismatch error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), info, reply(99));
+        match res {
+            Err(ContractError::CommentNotExists {}) => {}
+            _ => panic!("Must return comment not exists error"),
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetReplies { comment_id: 1, start_after: None, limit: None }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![2, 4], value.entries.iter().map(|c| c.comment_id).collect::<Vec<_>>());
+        assert_eq!(Some(1), value.entries[0].parent_comment_id);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetReplies { comment_id: 1, start_after: Some(2), limit: None }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.entries[0].comment_id);
+
+        // Whole thread tree
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentTree { thread_id: 1, parent_comment_id: None, depth: None, limit: None }).unwrap();
+        let value: CommentTreeResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 5], value.entries.iter().map(|n| n.comment.comment_id).collect::<Vec<_>>());
+        assert_eq!(2, value.entries[0].replies.len());
+        assert_eq!(3, value.entries[0].replies[0].replies[0].comment.comment_id);
+
+        // Depth limited subtree
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentTree { thread_id: 1, parent_comment_id: Some(1), depth: Some(1), limit: None }).unwrap();
+        let value: CommentTreeResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.entries.len());
+        assert!(value.entries[0].replies.is_empty());
+    }
+
+    #[test]
+    fn voting() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        let voter1 = mock_info("voter1", &coins(10000, "uluna"));
+        let voter2 = mock_info("voter2", &[]);
+        create_new_comment(deps.as_mut(), voter1.clone());
+
+        let vote = |target: Target, direction: Option<VoteDirection>| ExecuteMsg::Vote { target, id: 1, direction };
+        let _res = execute(deps.as_mut(), mock_env(), voter1.clone(), vote(Target::Thread, Some(VoteDirection::Up))).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), voter2.clone(), vote(Target::Thread, Some(VoteDirection::Up))).unwrap();
+        // Voting twice does not count twice
+        let _res = execute(deps.as_mut(), mock_env(), voter2.clone(), vote(Target::Thread, Some(VoteDirection::Up))).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!((2, 0), (value.upvotes, value.downvotes));
+
+        // Change and retract
+        let _res = execute(deps.as_mut(), mock_env(), voter2.clone(), vote(Target::Thread, Some(VoteDirection::Down))).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), voter1.clone(), vote(Target::Thread, None)).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!((0, 1), (value.upvotes, value.downvotes));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVote { voter: voter2.sender.clone(), target: Target::Thread, id: 1 }).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(VoteDirection::Down), value.direction);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVote { voter: voter1.sender.clone(), target: Target::Thread, id: 1 }).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.direction);
+
+        // Comment votes are kept apart from thread votes
+        let _res = execute(deps.as_mut(), mock_env(), voter2.clone(), vote(Target::Comment, Some(VoteDirection::Up))).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
+        let value: Comment = from_binary(&res).unwrap();
+        assert_eq!((1, 0), (value.upvotes, value.downvotes));
+
+        // Deleted content cannot be voted on
+        let _res = execute(deps.as_mut(), mock_env(), voter1.clone(), ExecuteMsg::DeleteComment { comment_id: 1, hard: None }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), voter1, vote(Target::Comment, Some(VoteDirection::Up)));
+        match res {
+            Err(ContractError::CommentDeleted {}) => {}
+            _ => panic!("Must return comment deleted error"),
+        }
+    }
+
+    #[test]
+    fn reactions() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        let admin_info = mock_info("creator", &[]);
+        let user1 = mock_info("user1", &[]);
+        let user2 = mock_info("user2", &[]);
+        let react = |reaction: &str| ExecuteMsg::React { target: Target::Thread, id: 1, reaction: String::from(reaction) };
+
+        // Allowlist is admin managed
+        let msg = ExecuteMsg::UpdateAllowedReactions { reactions: vec![String::from("heart"), String::from("rocket")] };
+        let res = execute(deps.as_mut(), mock_env(), user1.clone(), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), user1.clone(), react("poop"));
+        match res {
+            Err(ContractError::ReactionNotAllowed {}) => {}
+            _ => panic!("Must return reaction not allowed error"),
+        }
+
+        let _res = execute(deps.as_mut(), mock_env(), user1.clone(), react("heart")).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), user1.clone(), react("rocket")).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), user2.clone(), react("heart")).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetReactionCounts { target: Target::Thread, id: 1 }).unwrap();
+        let value: ReactionCountsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![ReactionCount { reaction: String::from("heart"), count: 2 }, ReactionCount { reaction: String::from("rocket"), count: 1 }], value.reactions);
+
+        // Reacting again toggles off, even after the reaction left the allowlist
+        let msg = ExecuteMsg::UpdateAllowedReactions { reactions: vec![String::from("heart")] };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), user1.clone(), react("rocket")).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetReactionsByAddress { address: user1.sender.clone(), target: Target::Thread, id: 1 }).unwrap();
+        let value: ReactionsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![String::from("heart")], value.reactions);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetReactionCounts { target: Target::Thread, id: 1 }).unwrap();
+        let value: ReactionCountsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.reactions.len());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAllowedReactions {}).unwrap();
+        let value: ReactionsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![String::from("heart")], value.reactions);
+
+        // Target must exist
+        let msg = ExecuteMsg::React { target: Target::Comment, id: 1, reaction: String::from("heart") };
+        let res = execute(deps.as_mut(), mock_env(), user2.clone(), msg.clone());
+        match res {
+            Err(ContractError::CommentNotExists {}) => {}
+            _ => panic!("Must return comment not exists error"),
+        }
+
+        // Allowlist keys must be distinct and not blank
+        for reactions in [vec![String::from("heart"), String::from("heart")], vec![String::from(" ")]] {
+            let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateAllowedReactions { reactions });
+            match res {
+                Err(ContractError::InvalidReaction { .. }) => {}
+                _ => panic!("Must return invalid reaction error"),
+            }
+        }
+
+        // Banned users cannot react, comments of deleted threads take no reactions
+        create_new_comment(deps.as_mut(), mock_info("commenter", &coins(10000, "uluna")));
+        let ban = ExecuteMsg::Ban { address: user1.sender.clone(), category: Some(String::from("General")), expires: None, reason: String::from("Spam") };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ban).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), user1, react("heart"));
+        match res {
+            Err(ContractError::Banned { .. }) => {}
+            _ => panic!("Must return banned error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::DeleteThread { id: 1, hard: None }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), user2, msg);
+        match res {
+            Err(ContractError::ThreadDeleted {}) => {}
+            _ => panic!("Must return thread deleted error"),
+        }
+    }
+
+    #[test]
+    fn tips() {
+        let mut deps = instantiate_contract();
+        create_new_thread(deps.as_mut());
+        let commenter = mock_info("commenter", &coins(10000, "uluna"));
+        create_new_comment(deps.as_mut(), commenter.clone());
+        let admin_info = mock_info("creator", &[]);
+        let tipper = mock_info("tipper", &[Coin { denom: String::from("uluna"), amount: Uint128::from(1000_u128) }, Coin { denom: String::from("uusd"), amount: Uint128::from(10_u128) }]);
+
+        // Cut cannot exceed the tip
+        let msg = ExecuteMsg::UpdateFees { thread_fee: None, comment_fee: None, tip_cut: Some(Decimal::percent(101)) };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidTipCut {}) => {}
+            _ => panic!("Must return invalid tip cut error"),
+        }
+        let msg = ExecuteMsg::UpdateFees { thread_fee: None, comment_fee: None, tip_cut: Some(Decimal::percent(10)) };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
+
+        // Author receives the tip minus the platform cut
+        let res = execute(deps.as_mut(), mock_env(), tipper.clone(), ExecuteMsg::Tip { target: Target::Comment, id: 1 }).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(CosmosMsg::Bank(BankMsg::Send {
+            to_address: commenter.sender.to_string(),
+            amount: vec![Coin { denom: String::from("uluna"), amount: Uint128::from(900_u128) }, Coin { denom: String::from("uusd"), amount: Uint128::from(9_u128) }],
+        }), res.messages[0].msg);
+        let _res = execute(deps.as_mut(), mock_env(), tipper.clone(), ExecuteMsg::Tip { target: Target::Thread, id: 1 }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTips { target: Target::Comment, id: 1 }).unwrap();
+        let value: TipsResponse = from_binary(&res).unwrap();
+        assert_eq!(tipper.funds, value.tips);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAuthorTips { author: Addr::unchecked("creator") }).unwrap();
+        let value: TipsResponse = from_binary(&res).unwrap();
+        assert_eq!(tipper.funds, value.tips);
+
+        // Tips need funds
+        let res = execute(deps.as_mut(), mock_env(), mock_info("tipper", &[]), ExecuteMsg::Tip { target: Target::Thread, id: 1 });
+        match res {
+            Err(ContractError::EmptyTip {}) => {}
+            _ => panic!("Must return empty tip error"),
+        }
+    }
+
+    #[test]
+    fn update_fees() {
+        let mut deps = instantiate_contract();
+
+        let auth_info = mock_info("creator", &coins(10000, "uluna"));
+        let update_fee_msg = ExecuteMsg::UpdateFees { thread_fee: Option::Some(coins(2, "uluna")), comment_fee: Option::Some(coins(2, "uluna")), tip_cut: None };
+       
+        // Update Without Authorized User
+        let un_auth_info = mock_info("anon", &coins(10000, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), un_auth_info.clone(), update_fee_msg.clone());
+
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Update With Authorized User
+        let _res = execute(deps.as_mut(), mock_env(), auth_info.clone(), update_fee_msg);
+
+        // Verify Updated Fees
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: Config = from_binary(&res).unwrap();
+        assert_eq!(coins(2, "uluna"), value.thread_fee);
+        assert_eq!(coins(2, "uluna"), value.comment_fee);
+
+        // Zero amounts and repeated denoms are rejected
+        let msg = ExecuteMsg::UpdateFees { thread_fee: Some(coins(0, "uluna")), comment_fee: None, tip_cut: None };
+        let res = execute(deps.as_mut(), mock_env(), auth_info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidFeeSchedule { denom }) => assert_eq!(String::from("uluna"), denom),
+            _ => panic!("Must return invalid fee schedule error"),
+        }
+        let msg = ExecuteMsg::UpdateFees { thread_fee: None, comment_fee: Some(vec![Coin { denom: String::from("uluna"), amount: Uint128::from(1_u128) }, Coin { denom: String::from("uluna"), amount: Uint128::from(2_u128) }]), tip_cut: None };
+        let res = execute(deps.as_mut(), mock_env(), auth_info, msg);
+        match res {
+            Err(ContractError::InvalidFeeSchedule { denom }) => assert_eq!(String::from("uluna"), denom),
+            _ => panic!("Must return invalid fee schedule error"),
+        }
+    }
+
+    #[test]
+    fn delete_thread() {
+        let mut deps = instantiate_contract();
+        let author_info = mock_info("author", &coins(10000, "uluna"));
+        let msg = ExecuteMsg::CreateThread { title: String::from("Spam"), content: String::from("Spam Content"), category: String::from("General")};
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg.clone()).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg).unwrap();
+        create_new_comment(deps.as_mut(), author_info.clone());
+
+        // Should return error if not executed by author or admin
+        let info = mock_info("anyone", &coins(2, "token"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::DeleteThread { id: 1, hard: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Soft delete by author keeps the tombstone and the comments
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::DeleteThread { id: 1, hard: None }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert!(value.deleted);
+        assert_eq!(String::from("Spam Content"), value.content);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1});
+        assert!(res.is_ok());
+
+        // Deleted thread is hidden from listings and cannot be updated
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), start_after: None, limit: None, order: None}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        assert_eq!(2, value.entries[0].id);
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::UpdateThreadTitle { id: 1, title: String::from("Back")});
+        match res {
+            Err(ContractError::ThreadDeleted {}) => {}
+            _ => panic!("Must return thread deleted error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::DeleteThread { id: 1, hard: None });
+        match res {
+            Err(ContractError::ThreadDeleted {}) => {}
+            _ => panic!("Must return thread deleted error"),
+        }
+
+        // Soft deleted thread can still be wiped, without counting it twice
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::UpdateThreadTitle { id: 2, title: String::from("Edited")}).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::DeleteThread { id: 2, hard: None }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::DeleteThread { id: 2, hard: Some(true) }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 2}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(String::new(), value.title);
+        assert_eq!(String::new(), value.content);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadRevisions { id: 2, start_after: None, limit: None }).unwrap();
+        let value: RevisionsResponse = from_binary(&res).unwrap();
+        assert!(value.entries.is_empty());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCounts {}).unwrap();
+        let value: CountsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, value.live_threads);
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::DeleteThread { id: 2, hard: Some(true) });
+        match res {
+            Err(ContractError::ThreadDeleted {}) => {}
+            _ => panic!("Must return thread deleted error"),
+        }
+        let msg = ExecuteMsg::CreateThread { title: String::from("Spam"), content: String::from("Spam Content"), category: String::from("General")};
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg).unwrap();
+
+        // Cascade policy is admin only
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::UpdateCascadePolicy { comment_cascade: CascadePolicy::Delete });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let admin_info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateCascadePolicy { comment_cascade: CascadePolicy::Delete }).unwrap();
+
+        // Hard delete by admin wipes the text and removes comments
+        let msg = ExecuteMsg::AddComment { thread_id: 3, comment: String::from("Spam Reply"), parent_comment_id: None};
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::DeleteThread { id: 3, hard: Some(true) }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 3}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert!(value.deleted);
+        assert_eq!(String::new(), value.title);
+        assert_eq!(String::new(), value.content);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 2});
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn delete_cascade_batches() {
+        let mut deps = instantiate_contract();
+        let admin_info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateCascadePolicy { comment_cascade: CascadePolicy::Delete }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateAllowedReactions { reactions: vec![String::from("heart")] }).unwrap();
+        create_new_thread(deps.as_mut());
+        for _ in 0..CASCADE_BATCH + 2 {
+            create_new_comment(deps.as_mut(), mock_info("commenter", &coins(10000, "uluna")));
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("fan", &[]), ExecuteMsg::React { target: Target::Comment, id: 1, reaction: String::from("heart") }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("fan", &coins(100, "uluna")), ExecuteMsg::Tip { target: Target::Comment, id: 1 }).unwrap();
+
+        // Purging needs a thread deleted under the Delete cascade
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::PurgeComments { thread_id: 1, limit: None });
+        match res {
+            Err(ContractError::NothingToPurge {}) => {}
+            _ => panic!("Must return nothing to purge error"),
+        }
+
+        // Deleting removes one batch, anyone can purge the rest
+        let res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::DeleteThread { id: 1, hard: None }).unwrap();
+        assert_eq!(("removed_comments", "30"), (res.attributes[4].key.as_str(), res.attributes[4].value.as_str()));
+        assert_eq!(("more_comments", "true"), (res.attributes[5].key.as_str(), res.attributes[5].value.as_str()));
+        assert!(REACTION_COUNTS.may_load(&deps.storage, (Target::Comment.key(), 1, "heart")).unwrap().is_none());
+        assert!(TIP_TOTALS.may_load(&deps.storage, (Target::Comment.key(), 1, "uluna")).unwrap().is_none());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::PurgeComments { thread_id: 1, limit: Some(1) }).unwrap();
+        assert_eq!(("more_comments", "true"), (res.attributes[4].key.as_str(), res.attributes[4].value.as_str()));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::PurgeComments { thread_id: 1, limit: None }).unwrap();
+        assert_eq!(("more_comments", "false"), (res.attributes[4].key.as_str(), res.attributes[4].value.as_str()));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::PurgeComments { thread_id: 1, limit: None });
+        match res {
+            Err(ContractError::NothingToPurge {}) => {}
+            _ => panic!("Must return nothing to purge error"),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCounts {}).unwrap();
+        let value: CountsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, value.live_comments);
+    }
+
+    #[test]
+    fn fee_schedule() {
+        let mut deps = instantiate_contract();
+        let admin_info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General")};
+
+        // Accept either uluna or an IBC stablecoin
+        let schedule = vec![Coin { denom: String::from("uluna"), amount: Uint128::from(100_u128) }, Coin { denom: String::from("ibc/usdc"), amount: Uint128::from(5_u128) }];
+        let update_fee_msg = ExecuteMsg::UpdateFees { thread_fee: Some(schedule.clone()), comment_fee: None, tip_cut: None };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), update_fee_msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: Config = from_binary(&res).unwrap();
+        assert_eq!(schedule, value.thread_fee);
+
+        // Instantiation validates the schedules too
+        let mut other = mock_dependencies_with_balance(&[]);
+        let instantiate_msg = InstantiateMsg { thread_fee: Some(vec![schedule[0].clone(), schedule[0].clone()]), comment_fee: None, comment_cascade: None, tip_cut: None };
+        let res = instantiate(other.as_mut(), mock_env(), admin_info.clone(), instantiate_msg);
+        match res {
+            Err(ContractError::InvalidFeeSchedule { denom }) => assert_eq!(String::from("uluna"), denom),
+            _ => panic!("Must return invalid fee schedule error"),
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(100, "uluna")), msg.clone()).unwrap();
+        assert_eq!(0, res.messages.len());
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(5, "ibc/usdc")), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(4, "ibc/usdc")), msg.clone());
+        match res {
+            Err(ContractError::LessFeeAmount {}) => {}
+            _ => panic!("Must return less fee amount error"),
+        }
+
+        // Overpayment and other accepted denoms are refunded
+        let funds = vec![Coin { denom: String::from("ibc/usdc"), amount: Uint128::from(7_u128) }, Coin { denom: String::from("uluna"), amount: Uint128::from(1000_u128) }];
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &funds), msg.clone()).unwrap();
+        let refund = vec![Coin { denom: String::from("ibc/usdc"), amount: Uint128::from(7_u128) }, Coin { denom: String::from("uluna"), amount: Uint128::from(900_u128) }];
+        assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: String::from("author"), amount: refund }), res.messages[0].msg);
+        assert_eq!(("fee_charged", "100uluna"), (res.attributes[4].key.as_str(), res.attributes[4].value.as_str()));
+        assert_eq!(("fee_refunded", "7ibc/usdc,900uluna"), (res.attributes[5].key.as_str(), res.attributes[5].value.as_str()));
+
+        // Unlisted denom is rejected instead of counting as zero
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uosmo")), msg);
+        match res {
+            Err(ContractError::InvalidFeeDenom { denom }) => assert_eq!(String::from("uosmo"), denom),
+            _ => panic!("Must return invalid fee denom error"),
+        }
+
+        // Send can withdraw any denom
+        deps.querier.update_balance(mock_env().contract.address, vec![Coin { denom: String::from("uosmo"), amount: Uint128::from(10_u128) }, Coin { denom: String::from("uusd"), amount: Uint128::from(5_u128) }]);
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::Send { address: Addr::unchecked("treasury"), amount: Uint128::from(5_u128), denom: String::from("uusd") }).unwrap();
+        assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: String::from("treasury"), amount: coins(5, "uusd") }), res.messages[0].msg);
+        let res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::Send { address: Addr::unchecked("treasury"), amount: Uint128::from(11_u128), denom: String::from("uosmo") });
+        match res {
+            Err(ContractError::NotEnoughBalance {}) => {}
+            _ => panic!("Must return not enough balance error"),
+        }
+    }
+
+    #[test]
+    fn cw20_fees() {
+        let mut deps = instantiate_contract();
+        let admin_info = mock_info("creator", &[]);
+        let token_info = mock_info("community_token", &[]);
+        let hook = |msg: &ReceiveMsg, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg { sender: String::from("author"), amount: Uint128::from(amount), msg: to_binary(msg).unwrap() });
+        let create_msg = ReceiveMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General") };
+
+        // Token must be whitelisted by the admin
+        let res = execute(deps.as_mut(), mock_env(), token_info.clone(), hook(&create_msg, 50));
+        match res {
+            Err(ContractError::Cw20TokenNotAccepted {}) => {}
+            _ => panic!("Must return cw20 token not accepted error"),
+        }
+        let msg = ExecuteMsg::UpdateCw20Fee { token: token_info.sender.clone(), thread_fee: Uint128::from(50_u128), comment_fee: Uint128::from(5_u128) };
+        let res = execute(deps.as_mut(), mock_env(), token_info.clone(), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCw20Fees {}).unwrap();
+        let value: Cw20FeesResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::from(50_u128), value.fees[0].thread_fee);
+
+        // Posting through the hook credits the original sender
+        let res = execute(deps.as_mut(), mock_env(), token_info.clone(), hook(&create_msg, 49));
+        match res {
+            Err(ContractError::LessFeeAmount {}) => {}
             _ => panic!("Must return less fee amount error"),
         }
-         // We should query thread response using id
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
-        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
-        assert_eq!(1, value.id);
-        assert_eq!(String::from("First Thread"), value.title);
-        assert_eq!(String::from("First Message"), value.content);
-        assert_eq!(String::from("General"), value.category);
+        let _res = execute(deps.as_mut(), mock_env(), token_info.clone(), hook(&create_msg, 50)).unwrap();
+        let comment_msg = ReceiveMsg::AddComment { thread_id: 1, comment: String::from("Comment"), parent_comment_id: None };
+        let res = execute(deps.as_mut(), mock_env(), token_info.clone(), hook(&comment_msg, 8)).unwrap();
+        assert_eq!(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("community_token"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("author"), amount: Uint128::from(3_u128) }).unwrap(),
+            funds: vec![],
+        }), res.messages[0].msg);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
+        let value: Comment = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("author"), value.author);
 
+        // Admin withdraws CW20 balance
+        deps.querier.update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_binary(&BalanceResponse { balance: Uint128::from(55_u128) }).unwrap())));
+        let msg = ExecuteMsg::SendCw20 { token: token_info.sender.clone(), address: Addr::unchecked("treasury"), amount: Uint128::from(56_u128) };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg);
+        match res {
+            Err(ContractError::NotEnoughBalance {}) => {}
+            _ => panic!("Must return not enough balance error"),
+        }
+        let msg = ExecuteMsg::SendCw20 { token: token_info.sender.clone(), address: Addr::unchecked("treasury"), amount: Uint128::from(55_u128) };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        assert_eq!(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("community_token"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("treasury"), amount: Uint128::from(55_u128) }).unwrap(),
+            funds: vec![],
+        }), res.messages[0].msg);
+
+        // Removed token is no longer accepted
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::RemoveCw20Fee { token: token_info.sender.clone() }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), token_info, hook(&create_msg, 50));
+        match res {
+            Err(ContractError::Cw20TokenNotAccepted {}) => {}
+            _ => panic!("Must return cw20 token not accepted error"),
+        }
     }
 
     #[test]
-    fn update_thread() {
+    fn fee_split() {
         let mut deps = instantiate_contract();
-        
-        create_new_thread(deps.as_mut());
+        let admin_info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CreateCategory { name: String::from("Anchor"), description: None, sort_order: None, owner: Some(Addr::unchecked("anchor_team")) };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
 
-        let updated_title = String::from("Updated Title");
-        let updated_content = String::from("Updated Content");
-
-        // Should return error if not executed by thread author
-        let info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThread{ id: 1, title: updated_title.clone(), content: updated_content.clone()};
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-        
+        // Shares cannot add up to more than the fee
+        let mut fee_split = FeeSplit { treasury: Some(Addr::unchecked("treasury")), treasury_share: Decimal::percent(20), thread_author_share: Decimal::percent(30), category_owner_share: Decimal::percent(60) };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateFeeSplit { fee_split: fee_split.clone() });
+        match res {
+            Err(ContractError::InvalidFeeSplit {}) => {}
+            _ => panic!("Must return invalid fee split error"),
+        }
+        fee_split.category_owner_share = Decimal::percent(10);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::UpdateFeeSplit { fee_split: fee_split.clone() });
         match res {
             Err(ContractError::Unauthorized {}) => {}
             _ => panic!("Must return unauthorized error"),
         }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UpdateFeeSplit { fee_split }).unwrap();
+
+        // Thread fees go to the treasury and category owner, comment fees also to the thread author
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("Anchor") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uluna")), msg).unwrap();
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Comment"), parent_comment_id: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("commenter", &coins(10000, "uluna")), msg).unwrap();
+        for (address, amount) in [("treasury", 4000), ("author", 3000), ("anchor_team", 2000)] {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::GetClaimable { address: Addr::unchecked(address) }).unwrap();
+            let value: ClaimableResponse = from_binary(&res).unwrap();
+            assert_eq!(coins(amount, "uluna"), value.native);
+        }
 
-        // Should update content for author
-        let info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThread { id: 1, title: updated_title.clone(), content: updated_content.clone()};
-        let _res = execute(deps.as_mut(), mock_env(), info, msg);
-
-         // Verify content is updated
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
-        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
-        assert_eq!(updated_title, value.title);
-        assert_eq!(updated_content, value.content);
+        // Accrued rewards are reserved from admin withdrawals
+        deps.querier.update_balance(mock_env().contract.address, coins(20000, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::Send { address: Addr::unchecked("creator"), amount: Uint128::from(11001_u128), denom: String::from("uluna") });
+        match res {
+            Err(ContractError::NotEnoughBalance {}) => {}
+            _ => panic!("Must return not enough balance error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::Send { address: Addr::unchecked("creator"), amount: Uint128::MAX, denom: String::from("uluna") });
+        match res {
+            Err(ContractError::NotEnoughBalance {}) => {}
+            _ => panic!("Must return not enough balance error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::Send { address: Addr::unchecked("creator"), amount: Uint128::from(11000_u128), denom: String::from("uluna") }).unwrap();
 
+        let res = execute(deps.as_mut(), mock_env(), mock_info("treasury", &[]), ExecuteMsg::ClaimRewards {}).unwrap();
+        assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: String::from("treasury"), amount: coins(4000, "uluna") }), res.messages[0].msg);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("treasury", &[]), ExecuteMsg::ClaimRewards {});
+        match res {
+            Err(ContractError::NothingToClaim {}) => {}
+            _ => panic!("Must return nothing to claim error"),
+        }
+        assert_eq!(Uint128::from(5000_u128), TOTAL_CLAIMABLE.load(deps.as_ref().storage, "uluna").unwrap());
+
+        // Owner can be removed again
+        let msg = ExecuteMsg::UpdateCategory { name: String::from("Anchor"), description: None, sort_order: None, archived: None, owner: None, clear_owner: Some(true) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCategories { include_archived: None }).unwrap();
+        let value: CategoriesResponse = from_binary(&res).unwrap();
+        let category = value.categories.iter().find(|category| category.name == "Anchor").unwrap();
+        assert_eq!(None, category.owner);
     }
+
     #[test]
-    fn update_thread_content() {
+    fn admin_transfer() {
         let mut deps = instantiate_contract();
-        
-        create_new_thread(deps.as_mut());
+        let admin_info = mock_info("creator", &[]);
+        let multisig_info = mock_info("multisig", &[]);
 
-        let updated_content = String::from("Updated Content!");
+        let res = execute(deps.as_mut(), mock_env(), multisig_info.clone(), ExecuteMsg::AcceptAdmin {});
+        match res {
+            Err(ContractError::NoPendingAdmin {}) => {}
+            _ => panic!("Must return no pending admin error"),
+        }
+        let msg = ExecuteMsg::ProposeNewAdmin { address: Addr::unchecked("multisig"), expires: Some(Expiration::AtHeight(mock_env().block.height + 10)) };
+        let res = execute(deps.as_mut(), mock_env(), multisig_info.clone(), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg.clone()).unwrap();
+        assert_eq!("admin_transfer_proposed", res.events[0].ty);
 
-        // Should return error if not executed by thread author
-        let info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThreadContent { id: 1, content: updated_content.clone()};
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-        
+        // Only the proposed address can accept, and only before the expiry
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::AcceptAdmin {});
         match res {
             Err(ContractError::Unauthorized {}) => {}
             _ => panic!("Must return unauthorized error"),
         }
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res = execute(deps.as_mut(), env, multisig_info.clone(), ExecuteMsg::AcceptAdmin {});
+        match res {
+            Err(ContractError::AdminTransferExpired {}) => {}
+            _ => panic!("Must return admin transfer expired error"),
+        }
 
-        // Should update content for author
-        let info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThreadContent { id: 1, content: updated_content.clone()};
-        let _res = execute(deps.as_mut(), mock_env(), info, msg);
+        // Cancelled proposal cannot be accepted
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::CancelAdminTransfer {}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPendingAdmin {}).unwrap();
+        let value: Option<PendingAdmin> = from_binary(&res).unwrap();
+        assert_eq!(None, value);
+        let res = execute(deps.as_mut(), mock_env(), multisig_info.clone(), ExecuteMsg::AcceptAdmin {});
+        match res {
+            Err(ContractError::NoPendingAdmin {}) => {}
+            _ => panic!("Must return no pending admin error"),
+        }
 
-         // Verify content is updated
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
-        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
-        assert_eq!(updated_content, value.content);
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), multisig_info.clone(), ExecuteMsg::AcceptAdmin {}).unwrap();
+        assert_eq!("admin_transfer_accepted", res.events[0].ty);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: Config = from_binary(&res).unwrap();
+        assert_eq!(Some(Addr::unchecked("multisig")), value.admin_addr);
+        let res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::CreateCategory { name: String::from("Anchor"), description: None, sort_order: None, owner: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
 
+        // Nobody holds admin rights after renouncing
+        let _res = execute(deps.as_mut(), mock_env(), multisig_info.clone(), ExecuteMsg::RenounceAdmin {}).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), multisig_info, ExecuteMsg::CreateCategory { name: String::from("Anchor"), description: None, sort_order: None, owner: None });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
     }
 
     #[test]
-    fn update_thread_title() {
+    fn content_limits() {
         let mut deps = instantiate_contract();
-        
-        create_new_thread(deps.as_mut());
+        let thread_msg = |title: &str, content: &str| ExecuteMsg::CreateThread { title: String::from(title), content: String::from(content), category: String::from("General") };
+        let info = mock_info("author", &coins(10000, "uluna"));
 
-        let updated_title = String::from("Updated Title");
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), thread_msg("  ", "Content"));
+        match res {
+            Err(ContractError::BlankField { field }) => assert_eq!("title", field),
+            _ => panic!("Must return blank field error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), thread_msg("Title", &"a".repeat(20001)));
+        match res {
+            Err(ContractError::TooLong { field, max }) => assert_eq!(("content", 20000), (field.as_str(), max)),
+            _ => panic!("Must return too long error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), thread_msg("Ti\u{0007}tle", "Content"));
+        match res {
+            Err(ContractError::ControlCharacter { field }) => assert_eq!("title", field),
+            _ => panic!("Must return control character error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), thread_msg("Title", "First line\nSecond line")).unwrap();
 
-        // Should return error if not executed by thread author
-        let info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: updated_title.clone()};
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-        
+        // Admin tightens the limits, edits are checked too
+        let mut content_limits = ContentLimits { comment: LengthLimit { min: 10, max: 5 }, ..ContentLimits::default() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::UpdateContentLimits { content_limits: content_limits.clone() });
+        match res {
+            Err(ContractError::InvalidContentLimits { field }) => assert_eq!("comment", field),
+            _ => panic!("Must return invalid content limits error"),
+        }
+        content_limits.comment = LengthLimit { min: 5, max: 10 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::UpdateContentLimits { content_limits: content_limits.clone() });
         match res {
             Err(ContractError::Unauthorized {}) => {}
             _ => panic!("Must return unauthorized error"),
         }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::UpdateContentLimits { content_limits }).unwrap();
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Hey"), parent_comment_id: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::TooShort { field, min }) => assert_eq!(("comment", 5), (field.as_str(), min)),
+            _ => panic!("Must return too short error"),
+        }
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Hello"), parent_comment_id: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Hello there!") });
+        match res {
+            Err(ContractError::TooLong { field, max }) => assert_eq!(("comment", 10), (field.as_str(), max)),
+            _ => panic!("Must return too long error"),
+        }
 
-        // Should update content for author
-        let info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::UpdateThreadTitle { id: 1, title: updated_title.clone()};
-        let _res = execute(deps.as_mut(), mock_env(), info, msg);
+        // Ban reasons have their own limit
+        let ban_msg = |reason: String| ExecuteMsg::Ban { address: Addr::unchecked("author"), category: None, expires: None, reason };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ban_msg("x".repeat(281)));
+        match res {
+            Err(ContractError::TooLong { field, max }) => assert_eq!(("reason", 280), (field.as_str(), max)),
+            _ => panic!("Must return too long error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ban_msg(String::from("Spam"))).unwrap();
+    }
 
-         // Verify content is updated
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
-        let value: GetThreadByIdResponse = from_binary(&res).unwrap();
-        assert_eq!(updated_title, value.title);
+    #[test]
+    fn migrate_legacy_config() {
+        let legacy = LegacyConfig {
+            thread_fee: Uint128::from(10_u128),
+            comment_fee: Uint128::zero(),
+            admin_addr: Addr::unchecked("creator"),
+        };
+        let legacy_deps = || {
+            let mut deps = instantiate_contract();
+            LEGACY_CONFIG.save(&mut deps.storage, &legacy).unwrap();
+            assert!(CONFIG.load(&deps.storage).is_err());
+            set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+            deps
+        };
 
+        // Fees take the denom passed to migrate, new settings start at their defaults
+        let mut deps = legacy_deps();
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: Some(String::from("uusd")) }).unwrap();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(coins(10, "uusd"), config.thread_fee);
+        assert!(config.comment_fee.is_empty());
+        assert_eq!(Some(Addr::unchecked("creator")), config.admin_addr);
+        assert_eq!(CascadePolicy::Retain, config.comment_cascade);
+        assert_eq!(Decimal::zero(), config.tip_cut);
+
+        // Without one they fall back to the 0.1.0 denom
+        let mut deps = legacy_deps();
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: None }).unwrap();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(coins(10, LEGACY_FEE_DENOM), config.thread_fee);
     }
 
     #[test]
-    fn add_comment() {
+    fn migrate_version_checks() {
         let mut deps = instantiate_contract();
-        let info = mock_info("creator", &coins(10000, "uluna"));
-        let comment = String::from("New Reply");
-        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: comment.clone()};
-        // Add Reply Without Creating Thread
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+
+        set_contract_version(&mut deps.storage, "crates.io:other_contract", "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: None });
         match res {
-            Err(ContractError::ThreadNotExists{}) => {}
-            _ => panic!("Must return thread not exists error"),
+            Err(ContractError::InvalidContractName { name }) => assert_eq!("crates.io:other_contract", name),
+            _ => panic!("Must return invalid contract name error"),
         }
 
-        create_new_thread(deps.as_mut());
-
-        // Check Fee Error
-        let fee_info = mock_info("creator", &coins(10, "uluna"));
-        let res = execute(deps.as_mut(), mock_env(), fee_info.clone(), msg.clone());
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: None });
         match res {
-            Err(ContractError::LessFeeAmount {  } ) => {}
-            _ => panic!("Must return less fee amount error"),
+            Err(ContractError::CannotDowngrade { .. }) => {}
+            _ => panic!("Must return cannot downgrade error"),
         }
-        // Add Reply After Creating Thread
-        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
 
-        // Verify Comment is added
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
-        let value: Comment = from_binary(&res).unwrap();
-        assert_eq!(1, value.thread_id);
+        // Steps for older releases are skipped and the stored version is bumped
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.2.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: None }).unwrap();
+        assert_eq!(3, res.attributes.len());
+        assert_eq!(CONTRACT_VERSION, get_contract_version(&deps.storage).unwrap().version);
+    }
+
+    #[test]
+    fn global_feeds() {
+        let mut deps = instantiate_contract();
+        create_new_category(deps.as_mut(), "Anchor");
+        create_new_thread(deps.as_mut());
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("Anchor") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uluna")), msg.clone()).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uluna")), msg).unwrap();
+        create_new_comment(deps.as_mut(), mock_info("creator", &coins(10000, "uluna")));
+        create_new_comment(deps.as_mut(), mock_info("author", &coins(10000, "uluna")));
+        let msg = ExecuteMsg::AddComment { thread_id: 2, comment: String::from("Comment"), parent_comment_id: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(10000, "uluna")), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::DeleteThread { id: 2, hard: None }).unwrap();
+
+        // Threads from every category, deleted ones skipped
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreads { start_after: None, limit: None, order: None }).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3, 1], value.entries.iter().map(|t| t.id).collect::<Vec<_>>());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreads { start_after: Some(1), limit: Some(1), order: Some(SortOrder::Asc) }).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.entries[0].id);
+        assert_eq!(Some(3), value.next_start_after);
 
+        // Comments of the deleted thread are skipped too
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetComments { start_after: None, limit: Some(1) }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.entries[0].comment_id);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetComments { start_after: value.next_start_after, limit: Some(1) }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].comment_id);
+
+        // Counters keep every id ever issued, live counts drop on delete
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::DeleteComment { comment_id: 1, hard: None }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCounts {}).unwrap();
+        let value: CountsResponse = from_binary(&res).unwrap();
+        assert_eq!(CountsResponse { thread_counter: 3, comment_counter: 3, live_threads: 2, live_comments: 2 }, value);
     }
 
     #[test]
-    fn update_comment() {
+    fn query_comments_by_author() {
         let mut deps = instantiate_contract();
         create_new_thread(deps.as_mut());
+        create_new_comment(deps.as_mut(), mock_info("author", &coins(10000, "uluna")));
+        create_new_comment(deps.as_mut(), mock_info("creator", &coins(10000, "uluna")));
+        create_new_comment(deps.as_mut(), mock_info("author", &coins(10000, "uluna")));
 
-        let auth_info = mock_info("creator", &coins(10000, "uluna"));
-        // Create A Comment
-        create_new_comment(deps.as_mut(), auth_info.clone());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByAuthor { author: Addr::unchecked("author"), start_after: None, limit: Some(1) }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.entries[0].comment_id);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByAuthor { author: Addr::unchecked("author"), start_after: value.next_start_after, limit: Some(1) }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].comment_id);
 
-        let update_comment_msg = ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Updated Comment")};
-       
-        // Update Without Authorized User
-        let un_auth_info = mock_info("anon", &coins(10000, "uluna"));
-        let res = execute(deps.as_mut(), mock_env(), un_auth_info.clone(), update_comment_msg.clone());
+        // Comments stored before the index existed are picked up by migrate
+        let comment = comments().load(&deps.storage, &1_u64.to_be_bytes()).unwrap();
+        comments().idx.author.remove(&mut deps.storage, &1_u64.to_be_bytes(), &comment).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByAuthor { author: Addr::unchecked("author"), start_after: None, limit: None }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries.len());
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy_fee_denom: None }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByAuthor { author: Addr::unchecked("author"), start_after: None, limit: None }).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3, 1], value.entries.iter().map(|c| c.comment_id).collect::<Vec<_>>());
+    }
 
+    #[test]
+    fn bans() {
+        let mut deps = instantiate_contract();
+        create_new_category(deps.as_mut(), "Anchor");
+        create_new_thread(deps.as_mut());
+        create_new_comment(deps.as_mut(), mock_info("author", &coins(10000, "uluna")));
+        let msg = ExecuteMsg::AddModerator { address: Addr::unchecked("general_mod"), categories: Some(vec![String::from("General")]) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mod_info = mock_info("general_mod", &[]);
+        let ban_msg = |category: Option<&str>, expires: Option<Expiration>| ExecuteMsg::Ban { address: Addr::unchecked("author"), category: category.map(String::from), expires, reason: String::from("Spam") };
+
+        // Scoped moderators can only ban inside their categories
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ban_msg(None, None));
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ban_msg(Some("Anchor"), None));
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let expires = Expiration::AtHeight(mock_env().block.height + 100);
+        let _res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ban_msg(Some("general"), Some(expires))).unwrap();
+
+        // Posting, editing and voting in the category are rejected
+        let author_info = mock_info("author", &coins(10000, "uluna"));
+        let general_thread = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General") };
+        let banned_msgs = vec![
+            general_thread.clone(),
+            ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Comment"), parent_comment_id: None },
+            ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Edited") },
+            ExecuteMsg::Vote { target: Target::Thread, id: 1, direction: Some(VoteDirection::Up) },
+            ExecuteMsg::Vote { target: Target::Comment, id: 1, direction: Some(VoteDirection::Down) },
+        ];
+        for msg in banned_msgs {
+            let res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg);
+            match res {
+                Err(ContractError::Banned { reason }) => assert_eq!("Spam", reason),
+                _ => panic!("Must return banned error"),
+            }
+        }
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("Anchor") };
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::UpdateThreadTitle { id: 2, title: String::from("Edited") }).unwrap();
+
+        // Ban lapses at its expiry
+        let mut env = mock_env();
+        env.block.height += 100;
+        let _res = execute(deps.as_mut(), env, author_info.clone(), general_thread.clone()).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBans { start_after: None, limit: None }).unwrap();
+        let value: BansResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(String::from("General")), value.bans[0].category);
+        assert_eq!(Addr::unchecked("general_mod"), value.bans[0].banned_by);
+
+        // Global ban by the admin, lifted with Unban
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ban_msg(None, None)).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), author_info.clone(), ExecuteMsg::UpdateThreadTitle { id: 2, title: String::from("Edited") });
+        match res {
+            Err(ContractError::Banned { .. }) => {}
+            _ => panic!("Must return banned error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ban_msg(Some("General"), Some(expires)));
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBans { start_after: None, limit: None }).unwrap();
+        let value: BansResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.bans[0].category);
+        let res = execute(deps.as_mut(), mock_env(), mod_info, ExecuteMsg::Unban { address: Addr::unchecked("author") });
         match res {
             Err(ContractError::Unauthorized {}) => {}
             _ => panic!("Must return unauthorized error"),
         }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::Unban { address: Addr::unchecked("author") }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), author_info, general_thread).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::Unban { address: Addr::unchecked("author") });
+        match res {
+            Err(ContractError::NotBanned {}) => {}
+            _ => panic!("Must return not banned error"),
+        }
 
-        // Update With Authorized User
-        let _res = execute(deps.as_mut(), mock_env(), auth_info.clone(), update_comment_msg);
+        // Moderators cannot lift their own ban, and only the admin acts on moderators
+        let msg = ExecuteMsg::AddModerator { address: Addr::unchecked("global_mod"), categories: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let global_mod_ban = ExecuteMsg::Ban { address: Addr::unchecked("global_mod"), category: Some(String::from("General")), expires: None, reason: String::from("Abuse") };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("general_mod", &[]), global_mod_ban.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), global_mod_ban).unwrap();
+        for sender in ["global_mod", "general_mod"] {
+            let res = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), ExecuteMsg::Unban { address: Addr::unchecked("global_mod") });
+            match res {
+                Err(ContractError::Unauthorized {}) => {}
+                _ => panic!("Must return unauthorized error"),
+            }
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::Unban { address: Addr::unchecked("global_mod") }).unwrap();
+    }
 
-        // Verify Updated Comment
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentById {id: 1}).unwrap();
-        let value: Comment = from_binary(&res).unwrap();
-        assert_eq!(String::from("Updated Comment"), value.comment);
+    #[test]
+    fn lock_thread() {
+        let mut deps = instantiate_contract();
+        let op_info = mock_info("op", &coins(10000, "uluna"));
+        let msg = ExecuteMsg::CreateThread { title: String::from("Title"), content: String::from("Content"), category: String::from("General") };
+        let _res = execute(deps.as_mut(), mock_env(), op_info.clone(), msg).unwrap();
+        create_new_comment(deps.as_mut(), mock_info("author", &coins(10000, "uluna")));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::LockThread { id: 1 });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // Thread author can lift their own lock
+        let _res = execute(deps.as_mut(), mock_env(), op_info.clone(), ExecuteMsg::LockThread { id: 1 }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(Some(Addr::unchecked("op")), value.locked_by);
+        let _res = execute(deps.as_mut(), mock_env(), op_info.clone(), ExecuteMsg::UnlockThread { id: 1 }).unwrap();
+
+        let msg = ExecuteMsg::AddModerator { address: Addr::unchecked("moderator"), categories: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("moderator", &[]), ExecuteMsg::LockThread { id: 1 }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::LockThread { id: 1 });
+        match res {
+            Err(ContractError::ThreadLocked {}) => {}
+            _ => panic!("Must return thread locked error"),
+        }
 
+        // No new comments or comment edits while locked
+        let msg = ExecuteMsg::AddComment { thread_id: 1, comment: String::from("Comment"), parent_comment_id: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uluna")), msg.clone());
+        match res {
+            Err(ContractError::ThreadLocked {}) => {}
+            _ => panic!("Must return thread locked error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::UpdateComment { comment_id: 1, comment: String::from("Edited") });
+        match res {
+            Err(ContractError::ThreadLocked {}) => {}
+            _ => panic!("Must return thread locked error"),
+        }
+
+        // A moderator lock holds against the thread author
+        let res = execute(deps.as_mut(), mock_env(), op_info, ExecuteMsg::UnlockThread { id: 1 });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::UnlockThread { id: 1 }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById {id: 1}).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert_eq!(None, value.locked_by);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("moderator", &[]), ExecuteMsg::UnlockThread { id: 1 });
+        match res {
+            Err(ContractError::ThreadNotLocked {}) => {}
+            _ => panic!("Must return thread not locked error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("author", &coins(10000, "uluna")), msg).unwrap();
     }
 
     #[test]
-    fn update_fees() {
+    fn pinned_threads() {
         let mut deps = instantiate_contract();
+        create_new_category(deps.as_mut(), "Anchor");
+        let admin_info = mock_info("creator", &[]);
+        for _ in 0..6 {
+            create_new_thread(deps.as_mut());
+        }
+        let msg = ExecuteMsg::AddModerator { address: Addr::unchecked("general_mod"), categories: Some(vec![String::from("General")]) };
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
 
-        let auth_info = mock_info("creator", &coins(10000, "uluna"));
-        let update_fee_msg = ExecuteMsg::UpdateFees { thread_fee: Option::Some(Uint128::from(2_u128)), comment_fee: Option::Some(Uint128::from(2_u128)) };
-       
-        // Update Without Authorized User
-        let un_auth_info = mock_info("anon", &coins(10000, "uluna"));
-        let res = execute(deps.as_mut(), mock_env(), un_auth_info.clone(), update_fee_msg.clone());
-
+        // Scoped moderators pin in their category but not globally
+        let mod_info = mock_info("general_mod", &[]);
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ExecuteMsg::PinThread { id: 1, scope: PinScope::Global });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("author", &[]), ExecuteMsg::PinThread { id: 1, scope: PinScope::Category });
         match res {
             Err(ContractError::Unauthorized {}) => {}
             _ => panic!("Must return unauthorized error"),
         }
+        for id in 1..=5 {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(id);
+            let _res = execute(deps.as_mut(), env, mod_info.clone(), ExecuteMsg::PinThread { id, scope: PinScope::Category }).unwrap();
+        }
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ExecuteMsg::PinThread { id: 6, scope: PinScope::Category });
+        match res {
+            Err(ContractError::TooManyPins { max }) => assert_eq!(5, max),
+            _ => panic!("Must return too many pins error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ExecuteMsg::PinThread { id: 5, scope: PinScope::Category });
+        match res {
+            Err(ContractError::ThreadAlreadyPinned {}) => {}
+            _ => panic!("Must return thread already pinned error"),
+        }
 
-        // Update With Authorized User
-        let _res = execute(deps.as_mut(), mock_env(), auth_info.clone(), update_fee_msg);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPinnedThreads { category: Some(String::from("general")) }).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![5, 4, 3, 2, 1], value.entries.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert!(value.entries[0].pinned);
+
+        // Thread stays pinned while any scope holds a pin
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::PinThread { id: 1, scope: PinScope::Global }).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mod_info.clone(), ExecuteMsg::UnpinThread { id: 1, scope: PinScope::Category }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById { id: 1 }).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert!(value.pinned);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPinnedThreads { category: None }).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].id);
+        let _res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UnpinThread { id: 1, scope: PinScope::Global }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById { id: 1 }).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert!(!value.pinned);
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::UnpinThread { id: 1, scope: PinScope::Global });
+        match res {
+            Err(ContractError::ThreadNotPinned {}) => {}
+            _ => panic!("Must return thread not pinned error"),
+        }
 
-        // Verify Updated Fees
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
-        let value: Config = from_binary(&res).unwrap();
-        assert_eq!(Uint128::from(2_u128), value.thread_fee);
-        assert_eq!(Uint128::from(2_u128), value.comment_fee);
+        // Moving a thread drops its category pin
+        let _res = execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::MoveThread { id: 2, category: String::from("Anchor") }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPinnedThreads { category: Some(String::from("General")) }).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![5, 4, 3], value.entries.iter().map(|t| t.id).collect::<Vec<_>>());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadById { id: 2 }).unwrap();
+        let value: Thread = from_binary(&res).unwrap();
+        assert!(!value.pinned);
     }
 
     #[test]
@@ -607,17 +3791,31 @@ mod tests {
         }
         
         // Query Threads With Pagination using Category Index
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), offset: Some(2_u64), limit: Some(10_u32)}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), start_after: Some(10_u64), limit: Some(10_u32), order: None}).unwrap();
         let value: ThreadsResponse = from_binary(&res).unwrap();
 
-        println!("{:?}", value.entries);
-
         // Verify Thread Vector
+        assert_eq!(9, value.entries[0].id);
         assert_eq!(1, value.entries[8].id);
         assert_eq!(title, value.entries[0].title);
         assert_eq!(content, value.entries[0].content);
         assert_eq!(category, value.entries[0].category);
         assert_eq!(9, value.entries.len());
+        assert_eq!(None, value.next_start_after);
+
+        // Walk the pages oldest first with the returned cursor
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), start_after: None, limit: Some(5_u32), order: Some(SortOrder::Asc)}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 5], value.entries.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(Some(5), value.next_start_after);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), start_after: value.next_start_after, limit: Some(5_u32), order: Some(SortOrder::Asc)}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![6, 7, 8, 9, 10], value.entries.iter().map(|t| t.id).collect::<Vec<_>>());
+
+        // Cursor past the latest thread does not underflow
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByCategory {category: String::from("General"), start_after: Some(100_u64), limit: Some(5_u32), order: None}).unwrap();
+        let value: ThreadsResponse = from_binary(&res).unwrap();
+        assert_eq!(11, value.entries[0].id);
 
     }
     #[test]
@@ -635,7 +3833,7 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info2.clone(), msg);
 
         // Query Threads With Pagination using Author Index
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByAuthor {author: info1.sender.clone(), offset: Some(0_u64), limit: Some(10_u32)}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetThreadsByAuthor {author: info1.sender.clone(), start_after: None, limit: Some(10_u32), order: None}).unwrap();
         let value: ThreadsResponse = from_binary(&res).unwrap();
 
         // Verify Thread Vector For Author1 Address
@@ -659,7 +3857,7 @@ mod tests {
         create_new_comment(deps.as_mut(), info2.clone());
 
         // Query Comments With Pagination using Thread Index
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByThread {thread_id: 1_u64, offset: Some(0_u64), limit: Some(10_u32)}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByThread {thread_id: 1_u64, start_after: None, limit: Some(10_u32), order: None}).unwrap();
         let value: CommentsResponse = from_binary(&res).unwrap();
 
         // Verify Index Vector for Comments
@@ -670,5 +3868,10 @@ mod tests {
         assert_eq!(info1.sender, value.entries[2].author);
         assert_eq!(3, value.entries.len());
 
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCommentsByThread {thread_id: 1_u64, start_after: Some(3_u64), limit: Some(1_u32), order: None}).unwrap();
+        let value: CommentsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.entries[0].comment_id);
+        assert_eq!(Some(2), value.next_start_after);
+
     }
 }
\ No newline at end of file
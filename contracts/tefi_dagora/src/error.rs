@@ -12,14 +12,90 @@ pub enum ContractError {
     ThreadExists {},
     #[error("ThreadNotExists")]
     ThreadNotExists {},
+    #[error("ThreadDeleted")]
+    ThreadDeleted {},
+    #[error("NothingToPurge")]
+    NothingToPurge {},
+    #[error("ThreadLocked")]
+    ThreadLocked {},
+    #[error("ThreadNotLocked")]
+    ThreadNotLocked {},
     #[error("CommentNotExists")]
     CommentNotExists {},
+    #[error("CommentDeleted")]
+    CommentDeleted {},
+    #[error("ParentCommentMismatch")]
+    ParentCommentMismatch {},
+    #[error("ReactionNotAllowed")]
+    ReactionNotAllowed {},
+    #[error("InvalidReaction: {reaction} is blank or listed twice")]
+    InvalidReaction { reaction: String },
     #[error("NotEnoughBalance")]
     NotEnoughBalance {},
     #[error("LessFeeAmount")]
     LessFeeAmount {},
+    #[error("InvalidFeeDenom: {denom} is not an accepted fee denom")]
+    InvalidFeeDenom { denom: String },
+    #[error("InvalidFeeSchedule: {denom} is zero or listed twice")]
+    InvalidFeeSchedule { denom: String },
+    #[error("Cw20TokenNotAccepted")]
+    Cw20TokenNotAccepted {},
+    #[error("InvalidFeeSplit")]
+    InvalidFeeSplit {},
+    #[error("NothingToClaim")]
+    NothingToClaim {},
+    #[error("NoPendingAdmin")]
+    NoPendingAdmin {},
+    #[error("AdminTransferExpired")]
+    AdminTransferExpired {},
+    #[error("InvalidContractName: {name}")]
+    InvalidContractName { name: String },
+    #[error("CannotDowngrade: stored version {stored} is newer than {current}")]
+    CannotDowngrade { stored: String, current: String },
+    #[error("InvalidVersion: {version}")]
+    InvalidVersion { version: String },
+    #[error("UpgradePending: run MigrateStep until the upgrade is done")]
+    UpgradePending {},
+    #[error("NoUpgradePending")]
+    NoUpgradePending {},
+    #[error("BlankField: {field} cannot be empty or whitespace")]
+    BlankField { field: String },
+    #[error("TooShort: {field} must be at least {min} characters")]
+    TooShort { field: String, min: u32 },
+    #[error("TooLong: {field} must be at most {max} characters")]
+    TooLong { field: String, max: u32 },
+    #[error("ControlCharacter: {field} contains control characters")]
+    ControlCharacter { field: String },
+    #[error("InvalidContentLimits: {field} minimum is above its maximum")]
+    InvalidContentLimits { field: String },
+    #[error("ThreadAlreadyPinned")]
+    ThreadAlreadyPinned {},
+    #[error("ThreadNotPinned")]
+    ThreadNotPinned {},
+    #[error("TooManyPins: at most {max} threads can be pinned per scope")]
+    TooManyPins { max: u32 },
+    #[error("InvalidTipCut")]
+    InvalidTipCut {},
+    #[error("EmptyTip")]
+    EmptyTip {},
     #[error("ConfigNotExists")]
     ConfigNotExists {},
+    #[error("Banned: {reason}")]
+    Banned { reason: String },
+    #[error("NotBanned")]
+    NotBanned {},
+    #[error("InvalidBanExpiry")]
+    InvalidBanExpiry {},
+    #[error("ModeratorNotExists")]
+    ModeratorNotExists {},
+    #[error("CategoryExists")]
+    CategoryExists {},
+    #[error("CategoryNotExists")]
+    CategoryNotExists {},
+    #[error("CategoryArchived")]
+    CategoryArchived {},
+    #[error("InvalidCategoryName")]
+    InvalidCategoryName {},
 
 
     #[error("Custom Error val: {val:?}")]
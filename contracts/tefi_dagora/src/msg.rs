@@ -1,15 +1,22 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Order, Uint128};
+use cw20::{Cw20Coin, Cw20ReceiveMsg};
+use cw_utils::Expiration;
 
-use crate::state::{Thread, Comment};
+use crate::state::{Ban, ContentLimits, Cw20Fee, FeeSplit, PinScope, Thread, Comment, CascadePolicy, Moderator, Category, Revision, Target, VoteDirection};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+   // Denom of the fees stored by 0.1.0, defaults to LEGACY_FEE_DENOM
+   pub legacy_fee_denom: Option<String>,
+}
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-   pub thread_fee: Option<Uint128>,
-   pub comment_fee: Option<Uint128>
+   pub thread_fee: Option<Vec<Coin>>,
+   pub comment_fee: Option<Vec<Coin>>,
+   pub comment_cascade: Option<CascadePolicy>,
+   pub tip_cut: Option<Decimal>
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -19,21 +26,92 @@ pub enum ExecuteMsg {
     UpdateThread {id: u64, title: String, content: String},
     UpdateThreadContent {id: u64, content: String},
     UpdateThreadTitle {id: u64, title: String},
-    AddComment {thread_id: u64, comment: String },
+    AddComment {thread_id: u64, comment: String, parent_comment_id: Option<u64> },
     UpdateComment {comment_id: u64, comment: String},
-    Send {address: Addr, amount: Uint128},
-    UpdateFees {thread_fee: Option<Uint128>, comment_fee: Option<Uint128>}
+    Send {address: Addr, amount: Uint128, denom: String},
+    UpdateFees {thread_fee: Option<Vec<Coin>>, comment_fee: Option<Vec<Coin>>, tip_cut: Option<Decimal>},
+    DeleteThread {id: u64, hard: Option<bool>},
+    // Removes the next batch of comments of a thread deleted under the Delete cascade
+    PurgeComments {thread_id: u64, limit: Option<u32>},
+    DeleteComment {comment_id: u64, hard: Option<bool>},
+    UpdateCascadePolicy {comment_cascade: CascadePolicy},
+    MoveThread {id: u64, category: String},
+    AddModerator {address: Addr, categories: Option<Vec<String>>},
+    RemoveModerator {address: Addr},
+    CreateCategory {name: String, description: Option<String>, sort_order: Option<u32>, owner: Option<Addr>},
+    // Clearing the owner takes precedence over setting one
+    UpdateCategory {name: String, description: Option<String>, sort_order: Option<u32>, archived: Option<bool>, owner: Option<Addr>, clear_owner: Option<bool>},
+    ArchiveCategory {name: String},
+    // A missing direction retracts the vote
+    Vote {target: Target, id: u64, direction: Option<VoteDirection>},
+    // Toggles the reaction for the sender
+    React {target: Target, id: u64, reaction: String},
+    UpdateAllowedReactions {reactions: Vec<String>},
+    // Forwards the attached funds to the author, minus the configured tip cut
+    Tip {target: Target, id: u64},
+    // CW20 send hook, pays the fee for the wrapped ReceiveMsg
+    Receive(Cw20ReceiveMsg),
+    UpdateCw20Fee {token: Addr, thread_fee: Uint128, comment_fee: Uint128},
+    RemoveCw20Fee {token: Addr},
+    SendCw20 {token: Addr, address: Addr, amount: Uint128},
+    UpdateFeeSplit {fee_split: FeeSplit},
+    ClaimRewards {},
+    ProposeNewAdmin {address: Addr, expires: Option<Expiration>},
+    AcceptAdmin {},
+    CancelAdminTransfer {},
+    RenounceAdmin {},
+    UpdateContentLimits {content_limits: ContentLimits},
+    PinThread {id: u64, scope: PinScope},
+    UnpinThread {id: u64, scope: PinScope},
+    LockThread {id: u64},
+    UnlockThread {id: u64},
+    // Bans everywhere when no category is given, and forever when no expiry is given
+    Ban {address: Addr, category: Option<String>, expires: Option<Expiration>, reason: String},
+    Unban {address: Addr},
+    // Continues the paged upgrade started by migrate, other messages are rejected until it is done
+    MigrateStep {limit: Option<u32>}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    CreateThread {title: String, content: String, category: String},
+    AddComment {thread_id: u64, comment: String, parent_comment_id: Option<u64> }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetThreadById {id: u64},
-    GetThreadsByCategory {category: String, offset: Option<u64>, limit: Option<u32>},
-    GetThreadsByAuthor {author: Addr, offset: Option<u64>, limit: Option<u32>},
+    GetThreadsByCategory {category: String, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>},
+    GetThreadsByAuthor {author: Addr, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>},
     GetCommentById {id: u64},
-    GetCommentsByThread {thread_id: u64, offset: Option<u64>, limit: Option<u32>},
-    GetConfig {}
+    GetCommentsByThread {thread_id: u64, start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>},
+    GetConfig {},
+    GetModerators {start_after: Option<Addr>, limit: Option<u32>},
+    GetModeratorsByCategory {category: String, start_after: Option<Addr>, limit: Option<u32>},
+    GetModeratorCategories {address: Addr},
+    GetCategories {include_archived: Option<bool>},
+    GetThreadRevisions {id: u64, start_after: Option<u32>, limit: Option<u32>},
+    GetCommentRevisions {comment_id: u64, start_after: Option<u32>, limit: Option<u32>},
+    GetReplies {comment_id: u64, start_after: Option<u64>, limit: Option<u32>},
+    GetCommentTree {thread_id: u64, parent_comment_id: Option<u64>, depth: Option<u32>, limit: Option<u32>},
+    GetVote {voter: Addr, target: Target, id: u64},
+    GetReactionCounts {target: Target, id: u64},
+    GetReactionsByAddress {address: Addr, target: Target, id: u64},
+    GetAllowedReactions {},
+    GetTips {target: Target, id: u64},
+    GetAuthorTips {author: Addr},
+    GetCw20Fees {},
+    GetClaimable {address: Addr},
+    GetPendingAdmin {},
+    GetThreads {start_after: Option<u64>, limit: Option<u32>, order: Option<SortOrder>},
+    GetComments {start_after: Option<u64>, limit: Option<u32>},
+    GetCounts {},
+    GetCommentsByAuthor {author: Addr, start_after: Option<u64>, limit: Option<u32>},
+    // Global pins when no category is given
+    GetPinnedThreads {category: Option<String>},
+    GetBans {start_after: Option<Addr>, limit: Option<u32>}
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct GetThreadByIdResponse {
@@ -44,12 +122,118 @@ pub struct GetThreadByIdResponse {
     pub author: Addr
 }
 
+// Listing order by id, newest first unless asked otherwise
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Order {
+        match order {
+            SortOrder::Asc => Order::Ascending,
+            SortOrder::Desc => Order::Descending,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ThreadsResponse {
     pub entries: Vec<Thread>,
+    // Pass as start_after to fetch the next page, None on the last page
+    pub next_start_after: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CommentsResponse {
-    pub entries: Vec<Comment>
+    pub entries: Vec<Comment>,
+    pub next_start_after: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ModeratorsResponse {
+    pub moderators: Vec<Moderator>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ModeratorCategoriesResponse {
+    pub scoped: bool,
+    pub categories: Vec<String>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoriesResponse {
+    pub categories: Vec<Category>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevisionsResponse {
+    pub entries: Vec<Revision>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommentNode {
+    pub comment: Comment,
+    pub replies: Vec<CommentNode>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommentTreeResponse {
+    pub entries: Vec<CommentNode>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteResponse {
+    pub direction: Option<VoteDirection>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReactionCount {
+    pub reaction: String,
+    pub count: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReactionCountsResponse {
+    pub reactions: Vec<ReactionCount>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReactionsResponse {
+    pub reactions: Vec<String>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TipsResponse {
+    pub tips: Vec<Coin>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20FeesResponse {
+    pub fees: Vec<Cw20Fee>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableResponse {
+    pub native: Vec<Coin>,
+    pub cw20: Vec<Cw20Coin>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CountsResponse {
+    // Highest ids issued so far
+    pub thread_counter: u64,
+    pub comment_counter: u64,
+    pub live_threads: u64,
+    // Comments not deleted themselves. Comments of a deleted thread stay counted until the
+    // Delete cascade removes them, uncounting them on thread delete would need a pass over the thread
+    pub live_comments: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BansResponse {
+    pub bans: Vec<Ban>
 }
\ No newline at end of file
@@ -1,17 +1,193 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
-use cw_storage_plus::{Item, MultiIndex, IndexList, Index, IndexedMap};
+use cosmwasm_std::{Addr, Coin, Decimal, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, MultiIndex, IndexList, Index, IndexedMap};
+use cw_utils::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+  // Accepted fee options, paying any one of them is enough
+  pub thread_fee: Vec<Coin>,
+  pub comment_fee: Vec<Coin>,
+  // None once the admin role has been renounced
+  pub admin_addr: Option<Addr>,
+  #[serde(default)]
+  pub comment_cascade: CascadePolicy,
+  // Share of every tip kept by the contract
+  #[serde(default)]
+  pub tip_cut: Decimal,
+  #[serde(default)]
+  pub fee_split: FeeSplit,
+  #[serde(default)]
+  pub content_limits: ContentLimits,
+}
+
+// Allowed length of a text field, counted in characters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LengthLimit {
+  pub min: u32,
+  pub max: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContentLimits {
+  pub title: LengthLimit,
+  pub content: LengthLimit,
+  pub category: LengthLimit,
+  pub comment: LengthLimit,
+  #[serde(default = "default_reason_limit")]
+  pub reason: LengthLimit,
+}
+
+fn default_reason_limit() -> LengthLimit {
+  LengthLimit { min: 1, max: 280 }
+}
+
+impl Default for ContentLimits {
+  fn default() -> Self {
+    ContentLimits {
+      title: LengthLimit { min: 1, max: 200 },
+      content: LengthLimit { min: 1, max: 20000 },
+      category: LengthLimit { min: 1, max: 64 },
+      comment: LengthLimit { min: 1, max: 5000 },
+      reason: default_reason_limit(),
+    }
+  }
+}
+
+// Shares of every charged fee credited as claimable rewards,
+// whatever is left over stays in the contract balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeeSplit {
+  pub treasury: Option<Addr>,
+  pub treasury_share: Decimal,
+  // Applies to comment fees only
+  pub thread_author_share: Decimal,
+  pub category_owner_share: Decimal,
+}
+
+// Config as stored by 0.1.0, read once by migrate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
   pub thread_fee: Uint128,
   pub comment_fee: Uint128,
   pub admin_addr: Addr,
 }
 
+// 0.1.0 charged its fees in uluna without storing the denom
+pub const LEGACY_FEE_DENOM: &str = "uluna";
+
+// What happens to the comments of a thread when the thread is deleted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadePolicy {
+    // Comments stay queryable by id and under the thread
+    #[default]
+    Retain,
+    // Comments are removed from storage together with their index entries
+    Delete,
+}
+
 pub const CONFIG: Item<Config> = Item::new("CONFIG");
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("CONFIG");
+
+// Cursor of the paged 0.2.0 upgrade, removed once every thread and comment is upgraded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpgradeProgress {
+  pub threads_done: bool,
+  // Last upgraded id of the current pass
+  pub last_id: Option<u64>,
+}
+
+pub const UPGRADE_PROGRESS: Item<UpgradeProgress> = Item::new("UPGRADE_PROGRESS");
+
+// Proposed admin waiting for AcceptAdmin
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+  pub address: Addr,
+  pub expires: Option<Expiration>,
+}
+
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("PENDING_ADMIN");
+
+// CW20 tokens accepted as fee payment through the Receive hook
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20Fee {
+  pub token: Addr,
+  pub thread_fee: Uint128,
+  pub comment_fee: Uint128,
+}
+
+pub const CW20_FEES: Map<&Addr, Cw20Fee> = Map::new("CW20_FEES");
+
+// Rewards accrued from the fee split, keyed by recipient and denom or token.
+// The totals are reserved so that admin withdrawals cannot spend them.
+pub const CLAIMABLE: Map<(&Addr, &str), Uint128> = Map::new("CLAIMABLE");
+pub const CLAIMABLE_CW20: Map<(&Addr, &Addr), Uint128> = Map::new("CLAIMABLE_CW20");
+pub const TOTAL_CLAIMABLE: Map<&str, Uint128> = Map::new("TOTAL_CLAIMABLE");
+pub const TOTAL_CLAIMABLE_CW20: Map<&Addr, Uint128> = Map::new("TOTAL_CLAIMABLE_CW20");
+
+// Category registry, keyed by the normalized name so that
+// "General", "general" and "General " share one entry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Category {
+  pub name: String,
+  pub description: String,
+  pub sort_order: u32,
+  pub archived: bool,
+  // Receives the category owner share of fees paid in this category
+  #[serde(default)]
+  pub owner: Option<Addr>,
+}
+
+pub const CATEGORIES: Map<&str, Category> = Map::new("CATEGORIES");
+
+pub fn category_key(name: &str) -> String {
+  name.trim().to_lowercase()
+}
+
+// Moderators can hide, lock and move content but cannot touch fees or funds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Moderator {
+  pub address: Addr,
+  // Scoped moderators only act on the categories listed in moderator_scopes()
+  #[serde(default)]
+  pub scoped: bool,
+}
+
+pub const MODERATORS: Map<&Addr, Moderator> = Map::new("MODERATORS");
+
+// Moderator Scope State and Indexed Map
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ModeratorScope {
+  pub moderator: Addr,
+  pub category: String,
+}
+
+const MODERATOR_SCOPE_NAMESPACE: &str = "moderator_scopes";
+
+pub struct ModeratorScopeIndexes<'a> {
+  pub category: MultiIndex<'a, String, ModeratorScope, (Addr, String)>,
+}
+
+impl<'a> IndexList<ModeratorScope> for ModeratorScopeIndexes<'a> {
+  fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ModeratorScope>> + '_> {
+    let v: Vec<&dyn Index<ModeratorScope>> = vec![&self.category];
+    Box::new(v.into_iter())
+  }
+}
+
+pub fn moderator_scopes<'a>() -> IndexedMap<'a, (Addr, String), ModeratorScope, ModeratorScopeIndexes<'a>> {
+  let indexes = ModeratorScopeIndexes {
+    category: MultiIndex::new(
+      |d: &ModeratorScope| d.category.clone(),
+      MODERATOR_SCOPE_NAMESPACE,
+      "moderator_scopes__category",
+    ),
+  };
+  IndexedMap::new(MODERATOR_SCOPE_NAMESPACE, indexes)
+}
 
 // Thread State and Indexed Map
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,6 +197,31 @@ pub struct Thread {
     pub content: String,
     pub author: Addr,
     pub category: String,
+    // Tombstone flag, deleted threads keep their id but are hidden from listings
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub created_height: u64,
+    #[serde(default)]
+    pub updated_at: Timestamp,
+    // Number of edits, previous versions live in REVISIONS
+    #[serde(default)]
+    pub revision: u32,
+    #[serde(default)]
+    pub upvotes: u64,
+    #[serde(default)]
+    pub downvotes: u64,
+    // Set while the thread is pinned globally or in its category
+    #[serde(default)]
+    pub pinned: bool,
+    // Locked threads take no new comments and their comments cannot be edited
+    #[serde(default)]
+    pub locked: bool,
+    // Who locked the thread, a lock set by anyone but the author can only be lifted by a moderator
+    #[serde(default)]
+    pub locked_by: Option<Addr>,
 }
 
 const THREAD_NAMESPACE: &str = "threads";
@@ -67,24 +268,61 @@ pub struct ThreadIndexes<'a> {
       pub comment: String,
       pub author: Addr,
       pub thread_id: u64,
+      // Deleted comments stay as placeholders so thread listings keep their shape
+      #[serde(default)]
+      pub deleted: bool,
+      #[serde(default)]
+      pub created_at: Timestamp,
+      #[serde(default)]
+      pub created_height: u64,
+      #[serde(default)]
+      pub updated_at: Timestamp,
+      #[serde(default)]
+      pub revision: u32,
+      // None for top level comments
+      #[serde(default)]
+      pub parent_comment_id: Option<u64>,
+      #[serde(default)]
+      pub upvotes: u64,
+      #[serde(default)]
+      pub downvotes: u64,
   }
   
 const COMMENT_NAMESPACE: &str = "comments";
 pub const COMMENT_COUNTER: Item<u64> = Item::new("comment_counter");
 
+// Threads and comments that are not deleted, unlike the counters which only grow
+pub const LIVE_THREADS: Item<u64> = Item::new("LIVE_THREADS");
+pub const LIVE_COMMENTS: Item<u64> = Item::new("LIVE_COMMENTS");
+
+pub fn adjust_live_count(store: &mut dyn Storage, count: &Item<u64>, added: u64, removed: u64) -> StdResult<u64> {
+    let live = (count.may_load(store)?.unwrap_or_default() + added).saturating_sub(removed);
+    count.save(store, &live)?;
+    Ok(live)
+}
+
 pub fn next_comment_counter(store: &mut dyn Storage) -> StdResult<u64> {
     let id: u64 = COMMENT_COUNTER.may_load(store)?.unwrap_or_default() + 1;
     COMMENT_COUNTER.save(store, &id)?;
     Ok(id)
 }
 
+// Parent index key, top level comments sit under parent id 0 of their thread
+pub fn comment_parent_key(thread_id: u64, parent_comment_id: Option<u64>) -> Vec<u8> {
+    let mut key = thread_id.to_be_bytes().to_vec();
+    key.extend_from_slice(&parent_comment_id.unwrap_or_default().to_be_bytes());
+    key
+}
+
 pub struct CommentIndexes<'a> {
     pub thread: MultiIndex<'a, Vec<u8>, Comment, Vec<u8>>,
+    pub parent: MultiIndex<'a, Vec<u8>, Comment, Vec<u8>>,
+    pub author: MultiIndex<'a, Addr, Comment, Vec<u8>>,
 }
 
 impl<'a> IndexList<Comment> for CommentIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Comment>> + '_> {
-      let v: Vec<&dyn Index<Comment>> = vec![&self.thread];
+      let v: Vec<&dyn Index<Comment>> = vec![&self.thread, &self.parent, &self.author];
       Box::new(v.into_iter())
     }
 }
@@ -96,6 +334,107 @@ pub fn comments<'a>() -> IndexedMap<'a, &'a [u8], Comment, CommentIndexes<'a>> {
         COMMENT_NAMESPACE,
         "comment__thread",
       ),
+      parent: MultiIndex::new(
+        |d: &Comment| comment_parent_key(d.thread_id, d.parent_comment_id),
+        COMMENT_NAMESPACE,
+        "comment__parent",
+      ),
+      author: MultiIndex::new(
+        |d: &Comment| d.author.clone(),
+        COMMENT_NAMESPACE,
+        "comment__author",
+      ),
     };
     IndexedMap::new(COMMENT_NAMESPACE, indexes)
-  }
\ No newline at end of file
+  }
+
+// Bans keep an address from posting, editing and voting until they expire.
+// Scoped bans only apply inside their category.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ban {
+  pub address: Addr,
+  pub category: Option<String>,
+  pub expires: Expiration,
+  pub reason: String,
+  pub banned_by: Addr,
+}
+
+pub const BANS: Map<&Addr, Ban> = Map::new("BANS");
+
+// Where a pinned thread is shown on top
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PinScope {
+    Category,
+    Global,
+}
+
+// Pins keyed by scope and thread id, holding the time of pinning.
+// Category pins use the normalized category name, global pins the empty string.
+pub const PINS: Map<(&str, u64), Timestamp> = Map::new("PINS");
+pub const GLOBAL_PIN_SCOPE: &str = "";
+pub const MAX_PINS: usize = 5;
+
+// Content kinds that other records can point at
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    Thread,
+    Comment,
+}
+
+impl Target {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Target::Thread => "thread",
+            Target::Comment => "comment",
+        }
+    }
+}
+
+// Revision log, keyed by (target, id, revision) and holding the text as it was before each edit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Revision {
+    pub revision: u32,
+    pub title: Option<String>,
+    pub content: String,
+    pub editor: Addr,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+}
+
+pub const REVISIONS: Map<(&str, u64, u32), Revision> = Map::new("REVISIONS");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteDirection {
+    Up,
+    Down,
+}
+
+impl VoteDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoteDirection::Up => "up",
+            VoteDirection::Down => "down",
+        }
+    }
+}
+
+// One vote per (target, id, voter), totals are kept on the voted entity
+pub const VOTES: Map<(&str, u64, &Addr), VoteDirection> = Map::new("VOTES");
+
+// Reaction keys the admin allows, e.g. "heart" or "rocket"
+pub const ALLOWED_REACTIONS: Item<Vec<String>> = Item::new("ALLOWED_REACTIONS");
+
+// Reactions applied by each address, keyed by (target, id, reactor)
+pub const REACTIONS: Map<(&str, u64, &Addr), Vec<String>> = Map::new("REACTIONS");
+
+// Counters keyed by (target, id, reaction), zero counts are removed
+pub const REACTION_COUNTS: Map<(&str, u64, &str), u64> = Map::new("REACTION_COUNTS");
+
+// Cumulative tips sent to a target, keyed by (target, id, denom)
+pub const TIP_TOTALS: Map<(&str, u64, &str), Uint128> = Map::new("TIP_TOTALS");
+
+// Cumulative tips sent to an author over all their posts, keyed by (author, denom)
+pub const AUTHOR_TIPS: Map<(&Addr, &str), Uint128> = Map::new("AUTHOR_TIPS");